@@ -0,0 +1,261 @@
+use soroban_sdk::{Address, BytesN, Env, Map, String, Vec};
+
+use crate::{
+    pools, trading::get_child_credits, utils, CarbonCredit, CreditEvent, CreditStatus, DataKey,
+    EventType, ReversalOutcome,
+};
+
+/// Basis points denominator for the buffer rate
+const BASIS_POINTS: i128 = 10_000;
+
+/// Set the share of each issuance withheld into the buffer pool, in basis points
+pub fn set_buffer_rate(env: &Env, rate_bps: u32) {
+    if rate_bps as i128 > BASIS_POINTS {
+        panic!("Buffer rate exceeds 100%");
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::BufferRate, &rate_bps);
+}
+
+/// Get the share of each issuance withheld into the buffer pool, in basis points
+pub fn get_buffer_rate(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::BufferRate)
+        .unwrap_or(0)
+}
+
+/// Get the buffer credits currently held by the registry
+pub fn get_pool_balance(env: &Env) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::BufferPool)
+        .unwrap_or(0)
+}
+
+/// Get the buffer credits withheld from an issuance
+pub fn get_contribution(env: &Env, credit_id: &BytesN<32>) -> i128 {
+    utils::get_persistent(env, &DataKey::BufferContribution(credit_id.clone())).unwrap_or(0)
+}
+
+/// Withhold the buffer share of a new issuance and open its full tonnage to
+/// reversals. Returns the quantity withheld; it enters the pool once the credit
+/// is active.
+pub fn withhold(env: &Env, credit_id: &BytesN<32>, quantity: i128) -> i128 {
+    utils::set_persistent(
+        env,
+        &DataKey::ReversibleTonnage(credit_id.clone()),
+        &quantity,
+    );
+    let withheld = quantity * get_buffer_rate(env) as i128 / BASIS_POINTS;
+    if withheld > 0 {
+        utils::set_persistent(
+            env,
+            &DataKey::BufferContribution(credit_id.clone()),
+            &withheld,
+        );
+    }
+    withheld
+}
+
+/// Move an activated issuance's withheld credits into the pool
+pub fn deposit(env: &Env, credit_id: &BytesN<32>) {
+    let withheld = get_contribution(env, credit_id);
+    if withheld > 0 {
+        set_pool_balance(env, get_pool_balance(env) + withheld);
+    }
+}
+
+/// Handle a reversal of `quantity` tons on the issuance `credit_id` belongs to.
+///
+/// Buffer credits are cancelled first. Any shortfall is clawed back from the
+/// issuance's un-retired lots in proportion to their holdings. Issuances still
+/// awaiting verification cannot be reversed, and reversals of an issuance never
+/// add up to more than the tons it issued, its withheld share included.
+pub fn reverse_credit(
+    env: &Env,
+    caller: &Address,
+    credit_id: &BytesN<32>,
+    quantity: i128,
+    reason: String,
+) -> ReversalOutcome {
+    caller.require_auth();
+    check_reversal_authority(env, caller);
+
+    if quantity <= 0 {
+        panic!("Quantity must be positive");
+    }
+
+    let credit: CarbonCredit = utils::get_persistent(env, &DataKey::Credit(credit_id.clone()))
+        .unwrap_or_else(|| panic!("Credit not found"));
+    // An unverified issuance has not deposited its buffer share, so it cannot draw on the pool
+    if credit.status == CreditStatus::PendingVerification {
+        panic!("Credit is awaiting verification");
+    }
+    let root_id = credit.lineage.first().unwrap_or(credit.id.clone());
+
+    // Cap the reversal at what the issuance itself put at risk
+    let reversible_key = DataKey::ReversibleTonnage(root_id.clone());
+    let reversible: i128 = utils::get_persistent(env, &reversible_key).unwrap_or(0);
+    if quantity > reversible {
+        panic!("Reversal exceeds issuance");
+    }
+    utils::set_persistent(env, &reversible_key, &(reversible - quantity));
+
+    // Cancel buffer credits first
+    let pool = get_pool_balance(env);
+    let buffer_cancelled = quantity.min(pool);
+    set_pool_balance(env, pool - buffer_cancelled);
+
+    // Claw back the rest from outstanding lots
+    let clawed_back = if quantity > buffer_cancelled {
+        claw_back(env, &root_id, quantity - buffer_cancelled, &reason)
+    } else {
+        0
+    };
+
+    let mut metadata = Map::new(env);
+    metadata.set(String::from_str(env, "reason"), reason);
+    record_reversal_event(
+        env,
+        &root_id,
+        Some(caller.clone()),
+        buffer_cancelled + clawed_back,
+        metadata,
+    );
+
+    ReversalOutcome {
+        buffer_cancelled,
+        clawed_back,
+    }
+}
+
+/// Only the admin or a registered verifier may record a reversal
+fn check_reversal_authority(env: &Env, caller: &Address) {
+    let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+    let is_verifier: bool = env
+        .storage()
+        .instance()
+        .get(&DataKey::Verifier(caller.clone()))
+        .unwrap_or(false);
+    if *caller != admin && !is_verifier {
+        panic!("Not authorized to record reversals");
+    }
+}
+
+/// Reduce the issuance's un-retired lots pro rata by up to `shortfall` tons.
+/// Returns the quantity clawed back.
+fn claw_back(env: &Env, root_id: &BytesN<32>, shortfall: i128, reason: &String) -> i128 {
+    let lots = outstanding_lots(env, root_id);
+    let mut outstanding = 0i128;
+    for lot in lots.iter() {
+        outstanding += lot.quantity;
+    }
+    let target = shortfall.min(outstanding);
+    if target == 0 {
+        return 0;
+    }
+
+    // Floor each lot's share, then spread the rounding remainder in lot order
+    let mut shares = Vec::new(env);
+    let mut assigned = 0i128;
+    for lot in lots.iter() {
+        let share = target * lot.quantity / outstanding;
+        shares.push_back(share);
+        assigned += share;
+    }
+    let mut remainder = target - assigned;
+    for (i, lot) in lots.iter().enumerate() {
+        if remainder == 0 {
+            break;
+        }
+        let share = shares.get(i as u32).unwrap();
+        let extra = remainder.min(lot.quantity - share);
+        shares.set(i as u32, share + extra);
+        remainder -= extra;
+    }
+
+    for (i, mut lot) in lots.iter().enumerate() {
+        let share = shares.get(i as u32).unwrap();
+        if share == 0 {
+            continue;
+        }
+        lot.quantity -= share;
+        if lot.quantity == 0 {
+            lot.status = CreditStatus::Reversed;
+        }
+        utils::set_persistent(env, &DataKey::Credit(lot.id.clone()), &lot);
+        // Lots in pool custody also reduce the pool's backing
+        pools::write_down(env, &lot.id, share);
+
+        let mut metadata = Map::new(env);
+        metadata.set(String::from_str(env, "reason"), reason.clone());
+        record_reversal_event(
+            env,
+            &lot.id,
+            Some(lot.current_owner.clone()),
+            share,
+            metadata,
+        );
+    }
+
+    target
+}
+
+/// The issuance and every lot split from it that still holds credits
+fn outstanding_lots(env: &Env, root_id: &BytesN<32>) -> Vec<CarbonCredit> {
+    let mut lots = Vec::new(env);
+    let mut pending = Vec::new(env);
+    pending.push_back(root_id.clone());
+
+    while let Some(lot_id) = pending.pop_front() {
+        let lot: CarbonCredit =
+            utils::get_persistent(env, &DataKey::Credit(lot_id.clone())).unwrap();
+        if lot.status != CreditStatus::Retired && lot.quantity > 0 {
+            lots.push_back(lot);
+        }
+        for child_id in get_child_credits(env, lot_id).iter() {
+            pending.push_back(child_id);
+        }
+    }
+
+    lots
+}
+
+fn set_pool_balance(env: &Env, balance: i128) {
+    env.storage().instance().set(&DataKey::BufferPool, &balance);
+}
+
+fn record_reversal_event(
+    env: &Env,
+    credit_id: &BytesN<32>,
+    from: Option<Address>,
+    quantity: i128,
+    metadata: Map<String, String>,
+) {
+    let event_count: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::EventCount(credit_id.clone()))
+        .unwrap_or(0);
+
+    let event = CreditEvent {
+        event_type: EventType::Reversal,
+        credit_id: credit_id.clone(),
+        timestamp: env.ledger().timestamp(),
+        from,
+        to: None,
+        quantity,
+        transaction_hash: BytesN::from_array(env, &[0u8; 32]),
+        metadata,
+    };
+
+    env.storage().instance().set(
+        &DataKey::CreditEvent(credit_id.clone(), event_count),
+        &event,
+    );
+    env.storage()
+        .instance()
+        .set(&DataKey::EventCount(credit_id.clone()), &(event_count + 1));
+}
@@ -1,6 +1,107 @@
-use soroban_sdk::{Address, Bytes, BytesN, Env, Map, String, Vec};
+use soroban_sdk::{Address, BytesN, Env, Map, String, Vec};
 
-use crate::{CarbonCredit, CreditEvent, CreditStatus, DataKey, EventType};
+use crate::{
+    buffer, utils, CarbonCredit, CreditEvent, CreditStatus, DataKey, EventType, IssuerProfile,
+};
+
+/// Check that `issuer` is an active registered issuer accredited for `standard`
+pub fn check_issuer(env: &Env, issuer: &Address, standard: &String) {
+    let profile: IssuerProfile = env
+        .storage()
+        .instance()
+        .get(&DataKey::Issuer(issuer.clone()))
+        .unwrap_or_else(|| panic!("Issuer not registered"));
+
+    if !profile.is_active {
+        panic!("Issuer is not active");
+    }
+    if !profile.verification_standards.contains(standard) {
+        panic!("Issuer not accredited for this standard");
+    }
+}
+
+/// Reserve a verification hash for `credit_id`, rejecting double issuance
+pub fn claim_verification_hash(env: &Env, verification_hash: &BytesN<32>, credit_id: &BytesN<32>) {
+    let key = DataKey::VerificationHash(verification_hash.clone());
+    if env.storage().persistent().has(&key) {
+        panic!("Verification hash already issued");
+    }
+    utils::set_persistent(env, &key, credit_id);
+}
+
+/// Get the credit issued against a verification hash
+pub fn get_credit_by_verification_hash(
+    env: &Env,
+    verification_hash: BytesN<32>,
+) -> Option<BytesN<32>> {
+    utils::get_persistent(env, &DataKey::VerificationHash(verification_hash))
+}
+
+/// Whether new credits wait for a second-party verifier before they can move
+pub fn verifier_approval_required(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::RequireVerifierApproval)
+        .unwrap_or(false)
+}
+
+/// Approve a credit awaiting verification and count it as issued
+pub fn approve_credit(env: &Env, verifier: &Address, credit_id: &BytesN<32>) {
+    verifier.require_auth();
+
+    let is_verifier: bool = env
+        .storage()
+        .instance()
+        .get(&DataKey::Verifier(verifier.clone()))
+        .unwrap_or(false);
+    if !is_verifier {
+        panic!("Verifier not registered");
+    }
+
+    let mut credit: CarbonCredit = utils::get_persistent(env, &DataKey::Credit(credit_id.clone()))
+        .unwrap_or_else(|| panic!("Credit not found"));
+
+    if credit.status != CreditStatus::PendingVerification {
+        panic!("Credit is not awaiting verification");
+    }
+    if credit.issuer == *verifier {
+        panic!("Issuer cannot verify its own credit");
+    }
+
+    credit.status = CreditStatus::Issued;
+    utils::set_persistent(env, &DataKey::Credit(credit_id.clone()), &credit);
+
+    record_credit_event(
+        env,
+        credit_id,
+        EventType::Verification,
+        Some(verifier.clone()),
+        Some(credit.issuer.clone()),
+        credit.quantity,
+    );
+    let withheld = buffer::get_contribution(env, credit_id);
+    record_issuance(env, credit_id, &credit.issuer, credit.quantity + withheld);
+}
+
+/// Add newly active credits to the issuer's and the registry's issued totals,
+/// and move their withheld buffer share into the pool
+pub fn record_issuance(env: &Env, credit_id: &BytesN<32>, issuer: &Address, quantity: i128) {
+    buffer::deposit(env, credit_id);
+
+    let key = DataKey::Issuer(issuer.clone());
+    let mut profile: IssuerProfile = env.storage().instance().get(&key).unwrap();
+    profile.total_issued += quantity;
+    env.storage().instance().set(&key, &profile);
+
+    let total_issued: i128 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TotalCreditsIssued)
+        .unwrap_or(0);
+    env.storage()
+        .instance()
+        .set(&DataKey::TotalCreditsIssued, &(total_issued + quantity));
+}
 
 /// Record a credit event in the history
 pub fn record_credit_event(
@@ -39,7 +140,7 @@ pub fn record_credit_event(
 
 /// Get credit status and details
 pub fn get_credit_status(env: &Env, credit_id: BytesN<32>) -> Option<CarbonCredit> {
-    env.storage().instance().get(&DataKey::Credit(credit_id))
+    utils::get_persistent(env, &DataKey::Credit(credit_id))
 }
 
 /// Get credit transaction history
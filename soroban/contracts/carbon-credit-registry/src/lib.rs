@@ -2,7 +2,10 @@
 
 use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Map, String, Vec};
 
+pub mod buffer;
 pub mod credits;
+pub mod pools;
+pub mod retirement;
 pub mod trading;
 pub mod utils;
 
@@ -25,6 +28,7 @@ pub struct CarbonCredit {
     pub status: CreditStatus,
     pub verification_hash: BytesN<32>, // Hash of verification documents
     pub metadata: Map<String, String>, // Additional project metadata
+    pub lineage: Vec<BytesN<32>>, // Ancestor lots, from the original issuance down to the direct parent
 }
 
 #[contracttype]
@@ -34,6 +38,8 @@ pub enum CreditStatus {
     Traded,
     Retired,
     Suspended,
+    PendingVerification, // Issued, awaiting second-party verifier approval
+    Reversed,            // Fully cancelled by a reversal
 }
 
 #[contracttype]
@@ -57,6 +63,31 @@ pub enum EventType {
     Retirement,
     Suspension,
     Verification,
+    Reversal,
+    PoolDeposit,
+    PoolRedemption,
+}
+
+/// Pool that wraps credits matching its criteria into a fungible token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreditPool {
+    pub id: u32,
+    pub token: Address, // SEP-41 pool token, one unit per ton
+    pub verification_standard: String,
+    pub vintage_start: u32,
+    pub vintage_end: u32,
+    pub project_types: Vec<String>, // Accepted project types; empty accepts any
+    pub balance: i128,              // Tons held in custody lots
+    pub supply: i128,               // Pool tokens outstanding
+}
+
+/// How a reversal was covered
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReversalOutcome {
+    pub buffer_cancelled: i128, // Tons cancelled from the buffer pool
+    pub clawed_back: i128,      // Tons cancelled from holders' un-retired lots
 }
 
 #[contracttype]
@@ -87,10 +118,36 @@ pub struct RetirementParams {
     pub credit_id: BytesN<32>,
     pub owner: Address,
     pub quantity: i128,
+    pub beneficiary: Beneficiary, // Party claiming the offset
+    pub reporting_period: String, // e.g. "FY2024"
     pub retirement_reason: String,
     pub retirement_certificate: BytesN<32>, // Hash of retirement certificate
 }
 
+/// Party on whose behalf credits are retired
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Beneficiary {
+    Account(Address),
+    Name(String),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetirementRecord {
+    pub id: u64,
+    pub credit_id: BytesN<32>,
+    pub owner: Address,
+    pub beneficiary: Beneficiary,
+    pub quantity: i128,
+    pub vintage_year: u32,
+    pub verification_standard: String,
+    pub reporting_period: String,
+    pub retirement_reason: String,
+    pub retirement_certificate: BytesN<32>,
+    pub timestamp: u64,
+}
+
 // Storage keys
 #[contracttype]
 pub enum DataKey {
@@ -105,6 +162,26 @@ pub enum DataKey {
     RetirementFeeRate, // Fee rate for retirement (in basis points)
     TotalCreditsIssued,
     TotalCreditsRetired,
+    ChildCredits(BytesN<32>),     // credit_id -> lots split off from it
+    Treasury,                     // Recipient of trading fees
+    VerificationHash(BytesN<32>), // verification_hash -> credit_id
+    Verifier(Address),
+    RequireVerifierApproval,
+    RetirementCount,
+    Retirement(u64),                              // record_id -> RetirementRecord
+    BeneficiaryRetirementCount(Beneficiary),      // beneficiary -> record count
+    BeneficiaryRetirement(Beneficiary, u32),      // beneficiary, position -> record_id
+    RetiredTonnage(u32, String),                  // vintage_year, standard -> tons retired
+    BeneficiaryTonnage(Beneficiary, u32, String), // beneficiary, vintage_year, standard -> tons retired
+    BufferRate,                                   // Share of issuance withheld (in basis points)
+    BufferPool,                                   // Buffer credits held by the registry
+    BufferContribution(BytesN<32>),               // credit_id -> tons withheld at issuance
+    ReversibleTonnage(BytesN<32>),                // credit_id -> issued tons not yet reversed
+    PoolCount,
+    Pool(u32),                   // pool_id -> CreditPool
+    PoolLotRange(u32),           // pool_id -> (oldest, next) custody lot positions
+    PoolLot(u32, u32),           // (pool_id, position) -> custody lot
+    PoolLotPosition(BytesN<32>), // custody lot -> (pool_id, position)
 }
 
 #[contract]
@@ -172,7 +249,49 @@ impl CarbonCreditRegistry {
             .set(&DataKey::IssuerCount, &issuer_count);
     }
 
-    /// Issue a new carbon credit
+    /// Activate or deactivate an issuer (admin only)
+    pub fn set_issuer_status(env: Env, issuer_address: Address, is_active: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let key = DataKey::Issuer(issuer_address);
+        let mut profile: IssuerProfile = env
+            .storage()
+            .instance()
+            .get(&key)
+            .unwrap_or_else(|| panic!("Issuer not registered"));
+        profile.is_active = is_active;
+        env.storage().instance().set(&key, &profile);
+    }
+
+    /// Register a second-party verifier (admin only)
+    pub fn register_verifier(env: Env, verifier: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&DataKey::Verifier(verifier), &true);
+    }
+
+    /// Require new credits to be approved by a verifier before they can move (admin only)
+    pub fn set_verifier_approval_required(env: Env, required: bool) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RequireVerifierApproval, &required);
+    }
+
+    /// Approve a credit awaiting verification
+    pub fn approve_credit(env: Env, verifier: Address, credit_id: BytesN<32>) {
+        credits::approve_credit(&env, &verifier, &credit_id)
+    }
+
+    /// Issue a new carbon credit. The issuer must be active and accredited for
+    /// the credit's standard, and each verification hash can be issued once.
+    #[allow(clippy::too_many_arguments)]
     pub fn issue_credit(
         env: Env,
         issuer: Address,
@@ -185,9 +304,18 @@ impl CarbonCreditRegistry {
         metadata: Map<String, String>,
     ) -> BytesN<32> {
         issuer.require_auth();
+        credits::check_issuer(&env, &issuer, &verification_standard);
+
+        if quantity <= 0 {
+            panic!("Quantity must be positive");
+        }
 
         // Generate unique credit ID
-        let credit_id = utils::generate_credit_id(&env, &issuer, &verification_hash);
+        let credit_id = utils::generate_credit_id(&env, &verification_hash);
+        credits::claim_verification_hash(&env, &verification_hash, &credit_id);
+
+        let pending = credits::verifier_approval_required(&env);
+        let withheld = buffer::withhold(&env, &credit_id, quantity);
 
         // Create the carbon credit
         let credit = CarbonCredit {
@@ -198,17 +326,20 @@ impl CarbonCreditRegistry {
             verification_standard,
             issuance_date: env.ledger().timestamp(),
             vintage_year,
-            quantity,
+            quantity: quantity - withheld,
             current_owner: issuer.clone(),
-            status: CreditStatus::Issued,
+            status: if pending {
+                CreditStatus::PendingVerification
+            } else {
+                CreditStatus::Issued
+            },
             verification_hash,
             metadata,
+            lineage: Vec::new(&env),
         };
 
         // Store the credit
-        env.storage()
-            .instance()
-            .set(&DataKey::Credit(credit_id.clone()), &credit);
+        utils::set_persistent(&env, &DataKey::Credit(credit_id.clone()), &credit);
 
         // Record issuance event
         credits::record_credit_event(
@@ -216,9 +347,12 @@ impl CarbonCreditRegistry {
             &credit_id,
             EventType::Issuance,
             None,
-            Some(issuer),
-            quantity,
+            Some(issuer.clone()),
+            quantity - withheld,
         );
+        if !pending {
+            credits::record_issuance(&env, &credit_id, &issuer, quantity);
+        }
 
         // Increment credit count
         let mut credit_count: u32 = env
@@ -234,36 +368,47 @@ impl CarbonCreditRegistry {
         credit_id
     }
 
-    /// Trade a carbon credit
-    pub fn trade_credit(env: Env, params: TradingParams) {
+    /// Set the address that receives trading fees (admin only)
+    pub fn set_treasury(env: Env, treasury: Address) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+    }
+
+    /// Get the address that receives trading fees
+    pub fn get_treasury(env: Env) -> Address {
+        trading::get_treasury(&env)
+    }
+
+    /// Trade a carbon credit for payment, splitting off a child lot for partial quantities.
+    /// Returns the id of the lot held by the buyer.
+    pub fn trade_credit(env: Env, params: TradingParams) -> BytesN<32> {
         trading::trade_credit(env, params)
     }
 
-    /// Retire a carbon credit
-    pub fn retire_credit(env: Env, params: RetirementParams) {
+    /// Retire a carbon credit on behalf of a beneficiary. Returns the
+    /// retirement record id.
+    pub fn retire_credit(env: Env, params: RetirementParams) -> u64 {
         trading::retire_credit(env, params)
     }
 
     /// Suspend a credit (admin only)
+    #[allow(unused_variables)]
     pub fn suspend_credit(env: Env, credit_id: BytesN<32>, reason: String) {
         let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
         admin.require_auth();
 
-        let mut credit: CarbonCredit = env
-            .storage()
-            .instance()
-            .get(&DataKey::Credit(credit_id.clone()))
-            .unwrap();
+        let mut credit: CarbonCredit =
+            utils::get_persistent(&env, &DataKey::Credit(credit_id.clone())).unwrap();
 
         credit.status = CreditStatus::Suspended;
-        env.storage()
-            .instance()
-            .set(&DataKey::Credit(credit_id.clone()), &credit);
+        utils::set_persistent(&env, &DataKey::Credit(credit_id.clone()), &credit);
     }
 
     /// Get credit status and details
     pub fn get_credit_status(env: Env, credit_id: BytesN<32>) -> Option<CarbonCredit> {
-        env.storage().instance().get(&DataKey::Credit(credit_id))
+        utils::get_persistent(&env, &DataKey::Credit(credit_id))
     }
 
     /// Get credit transaction history
@@ -288,6 +433,153 @@ impl CarbonCreditRegistry {
         events
     }
 
+    /// Get the lots split off from a credit by partial trades
+    pub fn get_child_credits(env: Env, credit_id: BytesN<32>) -> Vec<BytesN<32>> {
+        trading::get_child_credits(&env, credit_id)
+    }
+
+    /// Get the credit issued against a verification hash
+    pub fn get_credit_by_verification_hash(
+        env: Env,
+        verification_hash: BytesN<32>,
+    ) -> Option<BytesN<32>> {
+        credits::get_credit_by_verification_hash(&env, verification_hash)
+    }
+
+    /// Set the share of each issuance withheld into the buffer pool, in basis points (admin only)
+    pub fn set_buffer_rate(env: Env, rate_bps: u32) {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        buffer::set_buffer_rate(&env, rate_bps)
+    }
+
+    /// Get the share of each issuance withheld into the buffer pool, in basis points
+    pub fn get_buffer_rate(env: Env) -> u32 {
+        buffer::get_buffer_rate(&env)
+    }
+
+    /// Get the buffer credits held by the registry
+    pub fn get_buffer_pool_balance(env: Env) -> i128 {
+        buffer::get_pool_balance(&env)
+    }
+
+    /// Get the buffer credits withheld from an issuance
+    pub fn get_buffer_contribution(env: Env, credit_id: BytesN<32>) -> i128 {
+        buffer::get_contribution(&env, &credit_id)
+    }
+
+    /// Record a reversal against an issuance (admin or registered verifier).
+    /// Buffer credits are cancelled first, then un-retired lots pro rata.
+    pub fn reverse_credit(
+        env: Env,
+        caller: Address,
+        credit_id: BytesN<32>,
+        quantity: i128,
+        reason: String,
+    ) -> ReversalOutcome {
+        buffer::reverse_credit(&env, &caller, &credit_id, quantity, reason)
+    }
+
+    /// Create a pool wrapping credits of a standard, vintage range and
+    /// project types into `token` (admin only). The registry must be the
+    /// token's admin. Returns the pool id.
+    pub fn create_pool(
+        env: Env,
+        token: Address,
+        verification_standard: String,
+        vintage_start: u32,
+        vintage_end: u32,
+        project_types: Vec<String>,
+    ) -> u32 {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        pools::create_pool(
+            &env,
+            token,
+            verification_standard,
+            vintage_start,
+            vintage_end,
+            project_types,
+        )
+    }
+
+    /// Get a pool
+    pub fn get_pool(env: Env, pool_id: u32) -> Option<CreditPool> {
+        pools::get_pool(&env, pool_id)
+    }
+
+    /// Get the custody lots backing a pool, oldest deposit first
+    pub fn get_pool_lots(env: Env, pool_id: u32) -> Vec<BytesN<32>> {
+        pools::get_pool_lots(&env, pool_id)
+    }
+
+    /// Deposit credits into a pool and mint pool tokens at its current
+    /// backing. Returns the custody lot held by the pool.
+    pub fn deposit_to_pool(
+        env: Env,
+        owner: Address,
+        pool_id: u32,
+        credit_id: BytesN<32>,
+        quantity: i128,
+    ) -> BytesN<32> {
+        pools::deposit(&env, &owner, pool_id, &credit_id, quantity)
+    }
+
+    /// Burn pool tokens for their share of the underlying credits, taken from
+    /// `lots` in order or oldest deposits first when `lots` is empty. Returns
+    /// the redeemed lots.
+    pub fn redeem_from_pool(
+        env: Env,
+        redeemer: Address,
+        pool_id: u32,
+        amount: i128,
+        lots: Vec<BytesN<32>>,
+    ) -> Vec<BytesN<32>> {
+        pools::redeem(&env, &redeemer, pool_id, amount, lots)
+    }
+
+    /// Get a retirement record
+    pub fn get_retirement(env: Env, record_id: u64) -> Option<RetirementRecord> {
+        retirement::get_retirement(&env, record_id)
+    }
+
+    /// Get a page of a beneficiary's retirements, oldest first
+    pub fn get_beneficiary_retirements(
+        env: Env,
+        beneficiary: Beneficiary,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<RetirementRecord> {
+        retirement::get_beneficiary_retirements(&env, beneficiary, offset, limit)
+    }
+
+    /// Get the number of retirements made on behalf of a beneficiary
+    pub fn get_beneficiary_retirement_count(env: Env, beneficiary: Beneficiary) -> u32 {
+        retirement::get_beneficiary_retirement_count(&env, beneficiary)
+    }
+
+    /// Get the tons retired from credits of a vintage year under a standard
+    pub fn get_retired_tonnage(env: Env, vintage_year: u32, verification_standard: String) -> i128 {
+        retirement::get_retired_tonnage(&env, vintage_year, verification_standard)
+    }
+
+    /// Get the tons retired on behalf of a beneficiary from credits of a vintage year under a standard
+    pub fn get_beneficiary_retired_tonnage(
+        env: Env,
+        beneficiary: Beneficiary,
+        vintage_year: u32,
+        verification_standard: String,
+    ) -> i128 {
+        retirement::get_beneficiary_retired_tonnage(
+            &env,
+            beneficiary,
+            vintage_year,
+            verification_standard,
+        )
+    }
+
     /// Get issuer profile
     pub fn get_issuer_profile(env: Env, issuer_address: Address) -> Option<IssuerProfile> {
         env.storage()
@@ -0,0 +1,314 @@
+use soroban_sdk::{token, Address, BytesN, Env, String, Vec};
+
+use crate::{
+    credits::record_credit_event,
+    trading::{load_owned_credit, transfer_lot},
+    utils, CarbonCredit, CreditPool, CreditStatus, DataKey, EventType,
+};
+
+/// Create a pool that wraps credits matching its criteria into `token`.
+///
+/// `token` is a SEP-41 token administered by this registry, so the registry
+/// can mint it on deposit. Tokens are minted one per ton until a reversal
+/// writes the pool down; from then on every token is backed by
+/// `balance / supply` tons, so the loss is shared by all holders.
+pub fn create_pool(
+    env: &Env,
+    token: Address,
+    verification_standard: String,
+    vintage_start: u32,
+    vintage_end: u32,
+    project_types: Vec<String>,
+) -> u32 {
+    if vintage_start > vintage_end {
+        panic!("Invalid vintage range");
+    }
+
+    let pool_id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::PoolCount)
+        .unwrap_or(0);
+
+    let pool = CreditPool {
+        id: pool_id,
+        token,
+        verification_standard,
+        vintage_start,
+        vintage_end,
+        project_types,
+        balance: 0,
+        supply: 0,
+    };
+
+    save_pool(env, &pool);
+    env.storage()
+        .instance()
+        .set(&DataKey::PoolCount, &(pool_id + 1));
+
+    pool_id
+}
+
+/// Get a pool's criteria and the tons it holds
+pub fn get_pool(env: &Env, pool_id: u32) -> Option<CreditPool> {
+    env.storage().instance().get(&DataKey::Pool(pool_id))
+}
+
+/// Get the custody lots backing a pool, oldest deposit first
+pub fn get_pool_lots(env: &Env, pool_id: u32) -> Vec<BytesN<32>> {
+    let (first, next) = lot_range(env, pool_id);
+    let mut lots = Vec::new(env);
+    for position in first..next {
+        if let Some(lot_id) = utils::get_persistent(env, &DataKey::PoolLot(pool_id, position)) {
+            lots.push_back(lot_id);
+        }
+    }
+    lots
+}
+
+/// Write down the pool holding `lot_id` after a reversal removed `quantity`
+/// tons from it. Does nothing for lots outside pool custody.
+pub fn write_down(env: &Env, lot_id: &BytesN<32>, quantity: i128) {
+    let Some((pool_id, position)) =
+        utils::get_persistent::<(u32, u32)>(env, &DataKey::PoolLotPosition(lot_id.clone()))
+    else {
+        return;
+    };
+
+    let mut pool = load_pool(env, pool_id);
+    pool.balance -= quantity;
+    save_pool(env, &pool);
+
+    let lot: CarbonCredit = utils::get_persistent(env, &DataKey::Credit(lot_id.clone())).unwrap();
+    if lot.quantity == 0 {
+        remove_lot(env, pool_id, position, lot_id);
+    }
+}
+
+/// Deposit `quantity` tons of a lot into a pool and mint pool tokens to the
+/// owner at the pool's current backing. Returns the custody lot now held by
+/// the pool.
+pub fn deposit(
+    env: &Env,
+    owner: &Address,
+    pool_id: u32,
+    credit_id: &BytesN<32>,
+    quantity: i128,
+) -> BytesN<32> {
+    owner.require_auth();
+
+    let mut pool = load_pool(env, pool_id);
+    let credit = load_owned_credit(env, credit_id, owner);
+    if !matches_criteria(&pool, &credit) {
+        panic!("Credit does not match pool criteria");
+    }
+
+    // Depositors buy in at the written-down backing so they take none of an
+    // earlier loss; a pool written down to nothing takes no new deposits
+    let minted = if pool.supply == 0 {
+        quantity
+    } else if pool.balance == 0 {
+        panic!("Pool has no backing credits");
+    } else {
+        quantity * pool.supply / pool.balance
+    };
+    if minted <= 0 {
+        panic!("Quantity must be positive");
+    }
+
+    let custody = env.current_contract_address();
+    let lot_id = transfer_lot(env, credit_id, owner, &custody, quantity);
+    record_transfer(
+        env,
+        credit_id,
+        &lot_id,
+        EventType::PoolDeposit,
+        owner,
+        &custody,
+        quantity,
+    );
+
+    push_lot(env, pool_id, &lot_id);
+
+    pool.balance += quantity;
+    pool.supply += minted;
+    save_pool(env, &pool);
+
+    token::StellarAssetClient::new(env, &pool.token).mint(owner, &minted);
+
+    lot_id
+}
+
+/// Burn `amount` pool tokens and hand the redeemer their share of the
+/// underlying credits, `amount * balance / supply` tons rounded down.
+///
+/// Credits come from `lots` in the order given, or from the oldest deposits
+/// first when `lots` is empty. Returns the lots now held by the redeemer.
+pub fn redeem(
+    env: &Env,
+    redeemer: &Address,
+    pool_id: u32,
+    amount: i128,
+    lots: Vec<BytesN<32>>,
+) -> Vec<BytesN<32>> {
+    redeemer.require_auth();
+
+    if amount <= 0 {
+        panic!("Quantity must be positive");
+    }
+
+    let mut pool = load_pool(env, pool_id);
+    if amount > pool.supply {
+        panic!("Pool lots cannot cover redemption");
+    }
+    let tons = amount * pool.balance / pool.supply;
+    if tons == 0 {
+        panic!("Redemption too small for pool backing");
+    }
+
+    let fifo = lots.is_empty();
+    let candidates = if fifo {
+        get_pool_lots(env, pool_id)
+    } else {
+        lots
+    };
+
+    token::Client::new(env, &pool.token).burn(redeemer, &amount);
+
+    let custody = env.current_contract_address();
+    let mut received = Vec::new(env);
+    let mut remaining = tons;
+    for lot_id in candidates.iter() {
+        if remaining == 0 {
+            break;
+        }
+
+        let position = match utils::get_persistent::<(u32, u32)>(
+            env,
+            &DataKey::PoolLotPosition(lot_id.clone()),
+        ) {
+            Some((lot_pool, position)) if lot_pool == pool_id => position,
+            _ => panic!("Lot not held by pool"),
+        };
+        let lot: CarbonCredit =
+            utils::get_persistent(env, &DataKey::Credit(lot_id.clone())).unwrap();
+
+        // FIFO skips lots that cannot move; chosen lots must be redeemable
+        let movable = matches!(lot.status, CreditStatus::Issued | CreditStatus::Traded);
+        if fifo && (!movable || lot.quantity == 0) {
+            continue;
+        }
+
+        let take = remaining.min(lot.quantity);
+        let redeemed_id = transfer_lot(env, &lot_id, &custody, redeemer, take);
+        record_transfer(
+            env,
+            &lot_id,
+            &redeemed_id,
+            EventType::PoolRedemption,
+            &custody,
+            redeemer,
+            take,
+        );
+        if redeemed_id == lot_id {
+            remove_lot(env, pool_id, position, &lot_id);
+        }
+
+        received.push_back(redeemed_id);
+        remaining -= take;
+    }
+
+    if remaining > 0 {
+        panic!("Pool lots cannot cover redemption");
+    }
+
+    pool.balance -= tons;
+    pool.supply -= amount;
+    save_pool(env, &pool);
+
+    received
+}
+
+/// Positions of a pool's oldest custody lot and the next free slot
+fn lot_range(env: &Env, pool_id: u32) -> (u32, u32) {
+    utils::get_persistent(env, &DataKey::PoolLotRange(pool_id)).unwrap_or((0, 0))
+}
+
+/// Append a custody lot after the pool's newest deposit
+fn push_lot(env: &Env, pool_id: u32, lot_id: &BytesN<32>) {
+    let (first, next) = lot_range(env, pool_id);
+    utils::set_persistent(env, &DataKey::PoolLot(pool_id, next), lot_id);
+    utils::set_persistent(
+        env,
+        &DataKey::PoolLotPosition(lot_id.clone()),
+        &(pool_id, next),
+    );
+    utils::set_persistent(env, &DataKey::PoolLotRange(pool_id), &(first, next + 1));
+}
+
+/// Drop a custody lot that left the pool, moving the oldest position past
+/// any gap at the front so FIFO scans start at a held lot
+fn remove_lot(env: &Env, pool_id: u32, position: u32, lot_id: &BytesN<32>) {
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PoolLot(pool_id, position));
+    env.storage()
+        .persistent()
+        .remove(&DataKey::PoolLotPosition(lot_id.clone()));
+
+    let (mut first, next) = lot_range(env, pool_id);
+    while first < next
+        && !env
+            .storage()
+            .persistent()
+            .has(&DataKey::PoolLot(pool_id, first))
+    {
+        first += 1;
+    }
+    utils::set_persistent(env, &DataKey::PoolLotRange(pool_id), &(first, next));
+}
+
+fn matches_criteria(pool: &CreditPool, credit: &CarbonCredit) -> bool {
+    credit.verification_standard == pool.verification_standard
+        && credit.vintage_year >= pool.vintage_start
+        && credit.vintage_year <= pool.vintage_end
+        && (pool.project_types.is_empty() || pool.project_types.contains(&credit.project_type))
+}
+
+fn load_pool(env: &Env, pool_id: u32) -> CreditPool {
+    get_pool(env, pool_id).unwrap_or_else(|| panic!("Pool not found"))
+}
+
+fn save_pool(env: &Env, pool: &CreditPool) {
+    env.storage().instance().set(&DataKey::Pool(pool.id), pool);
+}
+
+/// Record a pool movement on the source lot and, when split, on the new lot
+fn record_transfer(
+    env: &Env,
+    source_id: &BytesN<32>,
+    lot_id: &BytesN<32>,
+    event_type: EventType,
+    from: &Address,
+    to: &Address,
+    quantity: i128,
+) {
+    record_credit_event(
+        env,
+        source_id,
+        event_type.clone(),
+        Some(from.clone()),
+        Some(to.clone()),
+        quantity,
+    );
+    if lot_id != source_id {
+        record_credit_event(
+            env,
+            lot_id,
+            event_type,
+            Some(from.clone()),
+            Some(to.clone()),
+            quantity,
+        );
+    }
+}
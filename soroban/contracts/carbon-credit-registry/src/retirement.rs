@@ -0,0 +1,118 @@
+use soroban_sdk::{Env, String, Vec};
+
+use crate::utils::{get_persistent, set_persistent};
+use crate::{Beneficiary, CarbonCredit, DataKey, RetirementParams, RetirementRecord};
+
+/// Store a retirement record and index it by beneficiary and by vintage/standard,
+/// both registry-wide and per beneficiary. Returns the record id.
+pub fn record_retirement(env: &Env, credit: &CarbonCredit, params: &RetirementParams) -> u64 {
+    let record_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::RetirementCount)
+        .unwrap_or(0);
+
+    let record = RetirementRecord {
+        id: record_id,
+        credit_id: params.credit_id.clone(),
+        owner: params.owner.clone(),
+        beneficiary: params.beneficiary.clone(),
+        quantity: params.quantity,
+        vintage_year: credit.vintage_year,
+        verification_standard: credit.verification_standard.clone(),
+        reporting_period: params.reporting_period.clone(),
+        retirement_reason: params.retirement_reason.clone(),
+        retirement_certificate: params.retirement_certificate.clone(),
+        timestamp: env.ledger().timestamp(),
+    };
+
+    set_persistent(env, &DataKey::Retirement(record_id), &record);
+    env.storage()
+        .instance()
+        .set(&DataKey::RetirementCount, &(record_id + 1));
+
+    // Append to the beneficiary's retirements
+    let count_key = DataKey::BeneficiaryRetirementCount(params.beneficiary.clone());
+    let count: u32 = get_persistent(env, &count_key).unwrap_or(0);
+    set_persistent(
+        env,
+        &DataKey::BeneficiaryRetirement(params.beneficiary.clone(), count),
+        &record_id,
+    );
+    set_persistent(env, &count_key, &(count + 1));
+
+    // Add to the tonnage retired for this vintage and standard
+    let tonnage_key =
+        DataKey::RetiredTonnage(credit.vintage_year, credit.verification_standard.clone());
+    let tonnage: i128 = get_persistent(env, &tonnage_key).unwrap_or(0);
+    set_persistent(env, &tonnage_key, &(tonnage + params.quantity));
+
+    let beneficiary_key = DataKey::BeneficiaryTonnage(
+        params.beneficiary.clone(),
+        credit.vintage_year,
+        credit.verification_standard.clone(),
+    );
+    let tonnage: i128 = get_persistent(env, &beneficiary_key).unwrap_or(0);
+    set_persistent(env, &beneficiary_key, &(tonnage + params.quantity));
+
+    record_id
+}
+
+/// Get a retirement record
+pub fn get_retirement(env: &Env, record_id: u64) -> Option<RetirementRecord> {
+    get_persistent(env, &DataKey::Retirement(record_id))
+}
+
+/// Get a page of a beneficiary's retirements, oldest first
+pub fn get_beneficiary_retirements(
+    env: &Env,
+    beneficiary: Beneficiary,
+    offset: u32,
+    limit: u32,
+) -> Vec<RetirementRecord> {
+    let count = get_beneficiary_retirement_count(env, beneficiary.clone());
+    let end = offset.saturating_add(limit).min(count);
+
+    let mut records = Vec::new(env);
+    for position in offset..end {
+        let record_id: u64 = get_persistent(
+            env,
+            &DataKey::BeneficiaryRetirement(beneficiary.clone(), position),
+        )
+        .unwrap();
+        if let Some(record) = get_retirement(env, record_id) {
+            records.push_back(record);
+        }
+    }
+
+    records
+}
+
+/// Number of retirements made on behalf of a beneficiary
+pub fn get_beneficiary_retirement_count(env: &Env, beneficiary: Beneficiary) -> u32 {
+    get_persistent(env, &DataKey::BeneficiaryRetirementCount(beneficiary)).unwrap_or(0)
+}
+
+/// Total tons retired from credits of a vintage year under a verification standard
+pub fn get_retired_tonnage(env: &Env, vintage_year: u32, verification_standard: String) -> i128 {
+    get_persistent(
+        env,
+        &DataKey::RetiredTonnage(vintage_year, verification_standard),
+    )
+    .unwrap_or(0)
+}
+
+/// Tons retired on behalf of a beneficiary from credits of a vintage year under a
+/// verification standard
+pub fn get_beneficiary_retired_tonnage(
+    env: &Env,
+    beneficiary: Beneficiary,
+    vintage_year: u32,
+    verification_standard: String,
+) -> i128 {
+    get_persistent(
+        env,
+        &DataKey::BeneficiaryTonnage(beneficiary, vintage_year, verification_standard),
+    )
+    .unwrap_or(0)
+}
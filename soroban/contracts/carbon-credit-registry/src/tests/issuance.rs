@@ -1,8 +1,11 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address, BytesN, Map, String, Vec};
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _, Ledger as _},
+    Address, BytesN, Map, String, Vec,
+};
 
-use crate::CreditStatus;
+use crate::{utils::PERSISTENT_BUMP_AMOUNT, CreditStatus, DataKey, EventType};
 
 use super::utils::*;
 
@@ -38,7 +41,7 @@ fn test_register_issuer() {
     let profile_data = profile.unwrap();
     assert_eq!(profile_data.address, issuer);
     assert_eq!(profile_data.name, issuer_name);
-    assert_eq!(profile_data.is_active, true);
+    assert!(profile_data.is_active);
     assert_eq!(profile_data.total_issued, 0);
     assert_eq!(profile_data.total_retired, 0);
 
@@ -277,3 +280,197 @@ fn test_issue_credit_with_metadata() {
     let credit = ctx.client.get_credit_status(&credit_id).unwrap();
     assert_eq!(credit.quantity, 1000);
 }
+
+// ============ ISSUANCE GOVERNANCE TESTS ============
+
+#[test]
+fn test_issuance_updates_issued_totals() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    issue_test_credit(&ctx, &issuer, 1000);
+    issue_test_credit(&ctx, &issuer, 500);
+
+    assert_contract_stats(&ctx, 1, 2, 1500, 0);
+    let profile = ctx.client.get_issuer_profile(&issuer).unwrap();
+    assert_eq!(profile.total_issued, 1500);
+}
+
+#[test]
+#[should_panic(expected = "Issuer not registered")]
+fn test_unregistered_issuer_cannot_issue() {
+    let ctx = setup_test_env();
+
+    let issuer = Address::generate(&ctx.env);
+    issue_test_credit(&ctx, &issuer, 1000);
+}
+
+#[test]
+#[should_panic(expected = "Issuer is not active")]
+fn test_deactivated_issuer_cannot_issue() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_issuer_status(&issuer, &false);
+    issue_test_credit(&ctx, &issuer, 1000);
+}
+
+#[test]
+fn test_reactivated_issuer_can_issue() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_issuer_status(&issuer, &false);
+    ctx.client.set_issuer_status(&issuer, &true);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    assert_credit_status(&ctx, &credit_id, CreditStatus::Issued);
+}
+
+#[test]
+#[should_panic(expected = "Issuer not accredited for this standard")]
+fn test_issuer_cannot_issue_unlisted_standard() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    issue_credit_with_hash(&ctx, &issuer, "Climate Action Reserve", 1000, 7);
+}
+
+#[test]
+#[should_panic(expected = "Quantity must be positive")]
+fn test_issue_zero_quantity() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    issue_test_credit(&ctx, &issuer, 0);
+}
+
+#[test]
+#[should_panic(expected = "Verification hash already issued")]
+fn test_verification_hash_issued_once() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    issue_credit_with_hash(&ctx, &issuer, "Verra", 1000, 7);
+
+    // A later ledger time must not allow the same documents to be issued again
+    ctx.env.ledger().with_mut(|li| li.timestamp += 1000);
+    issue_credit_with_hash(&ctx, &issuer, "Verra", 1000, 7);
+}
+
+#[test]
+#[should_panic(expected = "Verification hash already issued")]
+fn test_verification_hash_not_reusable_by_other_issuer() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let other = Address::generate(&ctx.env);
+    let mut standards = Vec::new(&ctx.env);
+    standards.push_back(String::from_str(&ctx.env, "Verra"));
+    ctx.client
+        .register_issuer(&other, &String::from_str(&ctx.env, "Other"), &standards);
+
+    issue_credit_with_hash(&ctx, &issuer, "Verra", 1000, 7);
+    issue_credit_with_hash(&ctx, &other, "Verra", 1000, 7);
+}
+
+#[test]
+fn test_credit_lookup_by_verification_hash() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_credit_with_hash(&ctx, &issuer, "Verra", 1000, 7);
+
+    let hash = BytesN::from_array(&ctx.env, &[7u8; 32]);
+    assert_eq!(
+        ctx.client.get_credit_by_verification_hash(&hash),
+        Some(credit_id)
+    );
+    let unknown = BytesN::from_array(&ctx.env, &[8u8; 32]);
+    assert_eq!(ctx.client.get_credit_by_verification_hash(&unknown), None);
+}
+
+#[test]
+fn test_verification_hash_claims_are_persistent() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    issue_credit_with_hash(&ctx, &issuer, "Verra", 1000, 7);
+
+    let key = DataKey::VerificationHash(BytesN::from_array(&ctx.env, &[7u8; 32]));
+    ctx.env.as_contract(&ctx.client.address, || {
+        assert!(!ctx.env.storage().instance().has(&key));
+        assert_eq!(
+            ctx.env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+    });
+}
+
+// ============ VERIFIER APPROVAL TESTS ============
+
+#[test]
+fn test_verifier_approval_flow() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let verifier = Address::generate(&ctx.env);
+    ctx.client.register_verifier(&verifier);
+    ctx.client.set_verifier_approval_required(&true);
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    assert_credit_status(&ctx, &credit_id, CreditStatus::PendingVerification);
+    assert_contract_stats(&ctx, 1, 1, 0, 0);
+
+    ctx.client.approve_credit(&verifier, &credit_id);
+    assert_credit_status(&ctx, &credit_id, CreditStatus::Issued);
+    assert_contract_stats(&ctx, 1, 1, 1000, 0);
+
+    let history = ctx.client.get_credit_history(&credit_id);
+    let event = history.get(1).unwrap();
+    assert_eq!(event.event_type, EventType::Verification);
+    assert_eq!(event.from, Some(verifier));
+
+    // Approved credits trade normally
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 1000);
+    assert_credit_owner(&ctx, &credit_id, &buyer);
+}
+
+#[test]
+#[should_panic(expected = "Credit is awaiting verification")]
+fn test_pending_credit_cannot_trade() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_verifier_approval_required(&true);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 100);
+}
+
+#[test]
+#[should_panic(expected = "Verifier not registered")]
+fn test_unregistered_verifier_cannot_approve() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_verifier_approval_required(&true);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let stranger = Address::generate(&ctx.env);
+    ctx.client.approve_credit(&stranger, &credit_id);
+}
+
+#[test]
+#[should_panic(expected = "Issuer cannot verify its own credit")]
+fn test_issuer_cannot_self_verify() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.register_verifier(&issuer);
+    ctx.client.set_verifier_approval_required(&true);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    ctx.client.approve_credit(&issuer, &credit_id);
+}
+
+#[test]
+#[should_panic(expected = "Credit is not awaiting verification")]
+fn test_approve_already_active_credit() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let verifier = Address::generate(&ctx.env);
+    ctx.client.register_verifier(&verifier);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    ctx.client.approve_credit(&verifier, &credit_id);
+}
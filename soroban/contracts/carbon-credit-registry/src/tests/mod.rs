@@ -1,6 +1,8 @@
 #![cfg(test)]
 
 pub mod issuance;
+pub mod pools;
 pub mod retirement;
+pub mod reversal;
 pub mod trading;
 pub mod utils;
@@ -0,0 +1,311 @@
+#![cfg(test)]
+
+use soroban_sdk::{testutils::Address as _, token, vec, Address, BytesN, String, Vec};
+
+use super::utils::*;
+
+/// Creates a pool token administered by the registry and a pool over it
+fn create_test_pool(
+    ctx: &TestContext,
+    standard: &str,
+    vintage_start: u32,
+    vintage_end: u32,
+    project_types: Vec<String>,
+) -> (u32, Address) {
+    let pool_token = ctx
+        .env
+        .register_stellar_asset_contract_v2(ctx.client.address.clone())
+        .address();
+    let pool_id = ctx.client.create_pool(
+        &pool_token,
+        &String::from_str(&ctx.env, standard),
+        &vintage_start,
+        &vintage_end,
+        &project_types,
+    );
+    (pool_id, pool_token)
+}
+
+fn pool_token_balance(ctx: &TestContext, pool_token: &Address, of: &Address) -> i128 {
+    token::Client::new(&ctx.env, pool_token).balance(of)
+}
+
+fn no_lots(ctx: &TestContext) -> Vec<BytesN<32>> {
+    Vec::new(&ctx.env)
+}
+
+// ============ DEPOSIT TESTS ============
+
+#[test]
+fn test_deposit_whole_lot_mints_pool_tokens() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let custody_lot = ctx
+        .client
+        .deposit_to_pool(&issuer, &pool_id, &credit_id, &1000);
+
+    assert_eq!(custody_lot, credit_id);
+    assert_credit_owner(&ctx, &credit_id, &ctx.client.address);
+    assert_eq!(pool_token_balance(&ctx, &pool_token, &issuer), 1000);
+
+    let pool = ctx.client.get_pool(&pool_id).unwrap();
+    assert_eq!(pool.balance, 1000);
+    assert_eq!(
+        ctx.client.get_pool_lots(&pool_id),
+        vec![&ctx.env, credit_id]
+    );
+}
+
+#[test]
+fn test_partial_deposit_splits_custody_lot() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let custody_lot = ctx
+        .client
+        .deposit_to_pool(&issuer, &pool_id, &credit_id, &400);
+
+    assert_ne!(custody_lot, credit_id);
+    assert_credit_owner(&ctx, &credit_id, &issuer);
+    assert_credit_quantity(&ctx, &credit_id, 600);
+    assert_credit_owner(&ctx, &custody_lot, &ctx.client.address);
+    assert_credit_quantity(&ctx, &custody_lot, 400);
+    assert_eq!(pool_token_balance(&ctx, &pool_token, &issuer), 400);
+}
+
+#[test]
+#[should_panic(expected = "Credit does not match pool criteria")]
+fn test_deposit_wrong_standard() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Gold Standard", 2020, 2025, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    ctx.client
+        .deposit_to_pool(&issuer, &pool_id, &credit_id, &1000);
+}
+
+#[test]
+#[should_panic(expected = "Credit does not match pool criteria")]
+fn test_deposit_vintage_outside_range() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2022, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000); // vintage 2024
+    ctx.client
+        .deposit_to_pool(&issuer, &pool_id, &credit_id, &1000);
+}
+
+#[test]
+fn test_deposit_filters_project_types() {
+    let (ctx, issuer) = setup_with_issuer();
+    let types = vec![&ctx.env, String::from_str(&ctx.env, "Reforestation")];
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2025, types);
+
+    let forest =
+        issue_credit_with_params(&ctx, &issuer, "Reforestation", "Brazil", "Verra", 2024, 100);
+    let wind =
+        issue_credit_with_params(&ctx, &issuer, "Wind Energy", "Denmark", "Verra", 2024, 100);
+
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &forest, &100);
+    assert!(ctx
+        .client
+        .try_deposit_to_pool(&issuer, &pool_id, &wind, &100)
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "Caller does not own this credit")]
+fn test_deposit_credit_not_owned() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let stranger = Address::generate(&ctx.env);
+    ctx.client
+        .deposit_to_pool(&stranger, &pool_id, &credit_id, &1000);
+}
+
+// ============ REDEMPTION TESTS ============
+
+#[test]
+fn test_fifo_redemption() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let first = issue_test_credit(&ctx, &issuer, 300);
+    let second = issue_test_credit(&ctx, &issuer, 500);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &first, &300);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &second, &500);
+
+    let received = ctx
+        .client
+        .redeem_from_pool(&issuer, &pool_id, &400, &no_lots(&ctx));
+
+    // Oldest lot is handed over whole, the next one is split
+    assert_eq!(received.len(), 2);
+    assert_eq!(received.get(0).unwrap(), first);
+    assert_credit_owner(&ctx, &first, &issuer);
+    let split = received.get(1).unwrap();
+    assert_credit_owner(&ctx, &split, &issuer);
+    assert_credit_quantity(&ctx, &split, 100);
+    assert_credit_quantity(&ctx, &second, 400);
+
+    assert_eq!(pool_token_balance(&ctx, &pool_token, &issuer), 400);
+    assert_eq!(ctx.client.get_pool(&pool_id).unwrap().balance, 400);
+    assert_eq!(ctx.client.get_pool_lots(&pool_id), vec![&ctx.env, second]);
+}
+
+#[test]
+fn test_redeem_chosen_lot() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let first = issue_test_credit(&ctx, &issuer, 300);
+    let second = issue_test_credit(&ctx, &issuer, 500);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &first, &300);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &second, &500);
+
+    let received =
+        ctx.client
+            .redeem_from_pool(&issuer, &pool_id, &200, &vec![&ctx.env, second.clone()]);
+
+    assert_eq!(received.len(), 1);
+    let lot = ctx
+        .client
+        .get_credit_status(&received.get(0).unwrap())
+        .unwrap();
+    assert_eq!(lot.lineage, vec![&ctx.env, second.clone()]);
+    assert_eq!(lot.quantity, 200);
+    assert_credit_quantity(&ctx, &first, 300);
+    assert_credit_quantity(&ctx, &second, 300);
+}
+
+#[test]
+fn test_pool_tokens_are_fungible() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    ctx.client
+        .deposit_to_pool(&issuer, &pool_id, &credit_id, &1000);
+
+    // A buyer acquires pool tokens on the open market and redeems them
+    let buyer = Address::generate(&ctx.env);
+    token::Client::new(&ctx.env, &pool_token).transfer(&issuer, &buyer, &50);
+
+    let received = ctx
+        .client
+        .redeem_from_pool(&buyer, &pool_id, &50, &no_lots(&ctx));
+    let lot_id = received.get(0).unwrap();
+    assert_credit_owner(&ctx, &lot_id, &buyer);
+    assert_credit_quantity(&ctx, &lot_id, 50);
+
+    // Redeemed credits can be retired
+    retire_test_credit(&ctx, lot_id.clone(), &buyer, 50);
+    assert_credit_quantity(&ctx, &lot_id, 0);
+}
+
+#[test]
+#[should_panic(expected = "Lot not held by pool")]
+fn test_redeem_lot_outside_pool() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let pooled = issue_test_credit(&ctx, &issuer, 300);
+    let outside = issue_test_credit(&ctx, &issuer, 300);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &pooled, &300);
+
+    ctx.client
+        .redeem_from_pool(&issuer, &pool_id, &100, &vec![&ctx.env, outside]);
+}
+
+#[test]
+#[should_panic(expected = "Pool lots cannot cover redemption")]
+fn test_chosen_lots_too_small() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, _) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let first = issue_test_credit(&ctx, &issuer, 300);
+    let second = issue_test_credit(&ctx, &issuer, 500);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &first, &300);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &second, &500);
+
+    ctx.client
+        .redeem_from_pool(&issuer, &pool_id, &400, &vec![&ctx.env, first]);
+}
+
+// ============ REVERSAL TESTS ============
+
+#[test]
+fn test_reversal_writes_down_pool_and_shares_loss() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let reversed = issue_test_credit(&ctx, &issuer, 600);
+    let intact = issue_test_credit(&ctx, &issuer, 400);
+    ctx.client
+        .deposit_to_pool(&issuer, &pool_id, &reversed, &600);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &intact, &400);
+    let holder = Address::generate(&ctx.env);
+    token::Client::new(&ctx.env, &pool_token).transfer(&issuer, &holder, &500);
+
+    // With no buffer the whole reversal is clawed back from the custody lot
+    ctx.client.reverse_credit(
+        &ctx.admin,
+        &reversed,
+        &600,
+        &String::from_str(&ctx.env, "Forest fire"),
+    );
+    let pool = ctx.client.get_pool(&pool_id).unwrap();
+    assert_eq!(pool.balance, 400);
+    assert_eq!(pool.supply, 1000);
+    assert_eq!(
+        ctx.client.get_pool_lots(&pool_id),
+        vec![&ctx.env, intact.clone()]
+    );
+
+    // Every token now redeems for 0.4 tons, so both holders share the loss
+    let received = ctx
+        .client
+        .redeem_from_pool(&holder, &pool_id, &500, &no_lots(&ctx));
+    assert_credit_quantity(&ctx, &received.get(0).unwrap(), 200);
+    let received = ctx
+        .client
+        .redeem_from_pool(&issuer, &pool_id, &500, &no_lots(&ctx));
+    assert_eq!(received, vec![&ctx.env, intact]);
+
+    let pool = ctx.client.get_pool(&pool_id).unwrap();
+    assert_eq!(pool.balance, 0);
+    assert_eq!(pool.supply, 0);
+    assert_eq!(ctx.client.get_pool_lots(&pool_id).len(), 0);
+}
+
+#[test]
+fn test_deposit_after_write_down_mints_at_backing() {
+    let (ctx, issuer) = setup_with_issuer();
+    let (pool_id, pool_token) = create_test_pool(&ctx, "Verra", 2020, 2025, Vec::new(&ctx.env));
+
+    let first = issue_test_credit(&ctx, &issuer, 1000);
+    ctx.client.deposit_to_pool(&issuer, &pool_id, &first, &1000);
+    ctx.client.reverse_credit(
+        &ctx.admin,
+        &first,
+        &500,
+        &String::from_str(&ctx.env, "Forest fire"),
+    );
+
+    // A ton deposited after the loss buys the tokens a ton now backs
+    let depositor = Address::generate(&ctx.env);
+    let second = issue_test_credit(&ctx, &issuer, 300);
+    trade_test_credit(&ctx, second.clone(), &issuer, &depositor, 300);
+    ctx.client
+        .deposit_to_pool(&depositor, &pool_id, &second, &300);
+    assert_eq!(pool_token_balance(&ctx, &pool_token, &depositor), 600);
+
+    let pool = ctx.client.get_pool(&pool_id).unwrap();
+    assert_eq!(pool.balance, 800);
+    assert_eq!(pool.supply, 1600);
+}
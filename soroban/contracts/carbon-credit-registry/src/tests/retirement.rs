@@ -1,8 +1,8 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address};
+use soroban_sdk::{testutils::Address as _, Address, BytesN, String};
 
-use crate::CreditStatus;
+use crate::{Beneficiary, CreditStatus, EventType};
 
 use super::utils::*;
 
@@ -117,8 +117,69 @@ fn test_retirement_history() {
     );
 }
 
+#[test]
+fn test_retire_split_lot() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 10_000);
+
+    // Buyer takes 50 tons and retires them
+    let buyer = Address::generate(&ctx.env);
+    let lot_id = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 50);
+    retire_test_credit(&ctx, lot_id.clone(), &buyer, 50);
+
+    assert_credit_status(&ctx, &lot_id, CreditStatus::Retired);
+    assert_credit_quantity(&ctx, &credit_id, 9_950);
+    assert_credit_status(&ctx, &credit_id, CreditStatus::Issued);
+
+    let (_, _, _, total_retired) = ctx.client.get_contract_stats();
+    assert_eq!(total_retired, 50);
+}
+
 // ============ EDGE CASE TESTS ============
 
+#[test]
+#[should_panic(expected = "Caller does not own this credit")]
+fn test_retire_credit_not_owned() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let stranger = Address::generate(&ctx.env);
+    retire_test_credit(&ctx, credit_id, &stranger, 100);
+}
+
+#[test]
+#[should_panic(expected = "Caller does not own this credit")]
+fn test_seller_cannot_retire_after_full_trade() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 1000);
+    retire_test_credit(&ctx, credit_id, &issuer, 1000);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient credit quantity")]
+fn test_retire_more_than_held() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    retire_test_credit(&ctx, credit_id, &issuer, 1500);
+}
+
+#[test]
+#[should_panic(expected = "Credit is retired")]
+fn test_retire_already_retired_credit() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    retire_test_credit(&ctx, credit_id.clone(), &issuer, 1000);
+    retire_test_credit(&ctx, credit_id, &issuer, 1);
+}
+
 #[test]
 #[should_panic]
 fn test_retire_non_existent_credit() {
@@ -296,3 +357,188 @@ fn test_mixed_operations_comprehensive() {
     let (_, _, _, total_retired) = ctx.client.get_contract_stats();
     assert_eq!(total_retired, 5000);
 }
+
+// ============ RETIREMENT REGISTRY TESTS ============
+
+#[test]
+fn test_retirement_record_stored() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let beneficiary = Beneficiary::Name(String::from_str(&ctx.env, "Acme Corp"));
+    let record_id =
+        retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 300, beneficiary.clone());
+
+    let record = ctx.client.get_retirement(&record_id).unwrap();
+    assert_eq!(record.credit_id, credit_id);
+    assert_eq!(record.owner, issuer);
+    assert_eq!(record.beneficiary, beneficiary);
+    assert_eq!(record.quantity, 300);
+    assert_eq!(record.vintage_year, 2024);
+    assert_eq!(
+        record.verification_standard,
+        String::from_str(&ctx.env, "Verra")
+    );
+    assert_eq!(
+        record.reporting_period,
+        String::from_str(&ctx.env, "FY2024")
+    );
+    assert_eq!(
+        record.retirement_certificate,
+        BytesN::from_array(&ctx.env, &[3u8; 32])
+    );
+
+    assert!(ctx.client.get_retirement(&(record_id + 1)).is_none());
+}
+
+#[test]
+fn test_beneficiary_retirements_listed() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let company = Address::generate(&ctx.env);
+    let beneficiary = Beneficiary::Account(company.clone());
+
+    // A broker retires on the company's behalf across several lots
+    let first = retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 100, beneficiary.clone());
+    retire_test_credit(&ctx, credit_id.clone(), &issuer, 50);
+    let second = retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 200, beneficiary.clone());
+    let third = retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 300, beneficiary.clone());
+
+    assert_eq!(ctx.client.get_beneficiary_retirement_count(&beneficiary), 3);
+
+    let all = ctx
+        .client
+        .get_beneficiary_retirements(&beneficiary, &0, &10);
+    assert_eq!(all.len(), 3);
+    assert_eq!(all.get(0).unwrap().id, first);
+    assert_eq!(all.get(1).unwrap().id, second);
+    assert_eq!(all.get(2).unwrap().id, third);
+
+    let page = ctx.client.get_beneficiary_retirements(&beneficiary, &1, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!(page.get(0).unwrap().quantity, 200);
+
+    let past_end = ctx
+        .client
+        .get_beneficiary_retirements(&beneficiary, &5, &10);
+    assert_eq!(past_end.len(), 0);
+
+    // The owner's own retirement is indexed separately
+    let own = Beneficiary::Account(issuer.clone());
+    assert_eq!(ctx.client.get_beneficiary_retirement_count(&own), 1);
+}
+
+#[test]
+fn test_retired_tonnage_by_vintage_and_standard() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let verra_2022 = issue_credit_with_params(
+        &ctx,
+        &issuer,
+        "Reforestation",
+        "Brazil",
+        "Verra",
+        2022,
+        1000,
+    );
+    let verra_2024 = issue_credit_with_params(
+        &ctx,
+        &issuer,
+        "Reforestation",
+        "Brazil",
+        "Verra",
+        2024,
+        1000,
+    );
+    let gold_2024 =
+        issue_credit_with_params(&ctx, &issuer, "Solar", "India", "Gold Standard", 2024, 1000);
+
+    retire_test_credit(&ctx, verra_2022, &issuer, 100);
+    retire_test_credit(&ctx, verra_2024.clone(), &issuer, 250);
+    retire_test_credit(&ctx, verra_2024, &issuer, 50);
+    retire_test_credit(&ctx, gold_2024, &issuer, 400);
+
+    let verra = String::from_str(&ctx.env, "Verra");
+    let gold = String::from_str(&ctx.env, "Gold Standard");
+    assert_eq!(ctx.client.get_retired_tonnage(&2022, &verra), 100);
+    assert_eq!(ctx.client.get_retired_tonnage(&2024, &verra), 300);
+    assert_eq!(ctx.client.get_retired_tonnage(&2024, &gold), 400);
+    assert_eq!(ctx.client.get_retired_tonnage(&2022, &gold), 0);
+
+    let profile = ctx.client.get_issuer_profile(&issuer).unwrap();
+    assert_eq!(profile.total_retired, 800);
+}
+
+#[test]
+fn test_retired_tonnage_by_beneficiary() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    let acme = Beneficiary::Name(String::from_str(&ctx.env, "Acme Corp"));
+    let globex = Beneficiary::Account(Address::generate(&ctx.env));
+
+    // Two companies retire from the same 2024 Verra issuance
+    retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 100, acme.clone());
+    retire_for_beneficiary(&ctx, credit_id.clone(), &issuer, 250, globex.clone());
+    retire_for_beneficiary(&ctx, credit_id, &issuer, 50, acme.clone());
+
+    let verra = String::from_str(&ctx.env, "Verra");
+    assert_eq!(
+        ctx.client
+            .get_beneficiary_retired_tonnage(&acme, &2024, &verra),
+        150
+    );
+    assert_eq!(
+        ctx.client
+            .get_beneficiary_retired_tonnage(&globex, &2024, &verra),
+        250
+    );
+    assert_eq!(
+        ctx.client
+            .get_beneficiary_retired_tonnage(&acme, &2022, &verra),
+        0
+    );
+    assert_eq!(ctx.client.get_retired_tonnage(&2024, &verra), 400);
+}
+
+#[test]
+fn test_retirement_event_records_certificate_hash() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    retire_test_credit(&ctx, credit_id.clone(), &issuer, 300);
+
+    let history = ctx.client.get_credit_history(&credit_id);
+    let event = history.get(history.len() - 1).unwrap();
+    assert_eq!(event.event_type, EventType::Retirement);
+    assert_eq!(
+        event
+            .metadata
+            .get(String::from_str(&ctx.env, "certificate"))
+            .unwrap(),
+        String::from_str(
+            &ctx.env,
+            "0303030303030303030303030303030303030303030303030303030303030303"
+        )
+    );
+}
+
+#[test]
+fn test_split_lot_retirement_keeps_vintage_and_standard() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 10_000);
+    let buyer = Address::generate(&ctx.env);
+    let lot_id = trade_test_credit(&ctx, credit_id, &issuer, &buyer, 50);
+    let record_id = retire_test_credit(&ctx, lot_id.clone(), &buyer, 50);
+
+    let record = ctx.client.get_retirement(&record_id).unwrap();
+    assert_eq!(record.credit_id, lot_id);
+    assert_eq!(record.vintage_year, 2024);
+    assert_eq!(
+        ctx.client
+            .get_retired_tonnage(&2024, &String::from_str(&ctx.env, "Verra")),
+        50
+    );
+}
@@ -0,0 +1,258 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _},
+    Address, String,
+};
+
+use crate::{utils::PERSISTENT_BUMP_AMOUNT, CreditStatus, DataKey, EventType, ReversalOutcome};
+
+use super::utils::*;
+
+fn reason(ctx: &TestContext) -> String {
+    String::from_str(&ctx.env, "Forest fire")
+}
+
+// ============ BUFFER POOL TESTS ============
+
+#[test]
+fn test_buffer_withheld_at_issuance() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_buffer_rate(&1_000); // 10%
+    assert_eq!(ctx.client.get_buffer_rate(), 1_000);
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    assert_credit_quantity(&ctx, &credit_id, 900);
+    assert_eq!(ctx.client.get_buffer_contribution(&credit_id), 100);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 100);
+
+    // Issued totals count the gross issuance, buffer included
+    assert_contract_stats(&ctx, 1, 1, 1000, 0);
+}
+
+#[test]
+fn test_buffer_contribution_is_persistent() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_buffer_rate(&1_000);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let key = DataKey::BufferContribution(credit_id);
+    ctx.env.as_contract(&ctx.client.address, || {
+        assert!(!ctx.env.storage().instance().has(&key));
+        assert_eq!(
+            ctx.env.storage().persistent().get_ttl(&key),
+            PERSISTENT_BUMP_AMOUNT
+        );
+    });
+}
+
+#[test]
+fn test_buffer_enters_pool_on_approval() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let verifier = Address::generate(&ctx.env);
+    ctx.client.register_verifier(&verifier);
+    ctx.client.set_verifier_approval_required(&true);
+    ctx.client.set_buffer_rate(&2_000); // 20%
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 500);
+    assert_credit_quantity(&ctx, &credit_id, 400);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 0);
+
+    ctx.client.approve_credit(&verifier, &credit_id);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 100);
+    assert_contract_stats(&ctx, 1, 1, 500, 0);
+}
+
+#[test]
+#[should_panic(expected = "Buffer rate exceeds 100%")]
+fn test_buffer_rate_above_full_issuance() {
+    let ctx = setup_test_env();
+
+    ctx.client.set_buffer_rate(&10_001);
+}
+
+// ============ REVERSAL TESTS ============
+
+#[test]
+fn test_reversal_covered_by_buffer() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_buffer_rate(&1_000);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let outcome = ctx
+        .client
+        .reverse_credit(&ctx.admin, &credit_id, &60, &reason(&ctx));
+    assert_eq!(
+        outcome,
+        ReversalOutcome {
+            buffer_cancelled: 60,
+            clawed_back: 0,
+        }
+    );
+
+    // Holders keep their credits; the pool absorbs the loss
+    assert_credit_quantity(&ctx, &credit_id, 900);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 40);
+
+    let history = ctx.client.get_credit_history(&credit_id);
+    let event = history.get(history.len() - 1).unwrap();
+    assert_eq!(event.event_type, EventType::Reversal);
+    assert_eq!(event.from, Some(ctx.admin.clone()));
+    assert_eq!(event.quantity, 60);
+    assert_eq!(
+        event.metadata.get(String::from_str(&ctx.env, "reason")),
+        Some(reason(&ctx))
+    );
+}
+
+#[test]
+fn test_reversal_claws_back_pro_rata() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_buffer_rate(&1_000);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    // Issuer keeps 600, buyer holds 300 and retires 100 of it
+    let buyer = Address::generate(&ctx.env);
+    let lot_id = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 300);
+    retire_test_credit(&ctx, lot_id.clone(), &buyer, 100);
+
+    // Reported against the buyer's lot, applied to the whole issuance
+    let outcome = ctx
+        .client
+        .reverse_credit(&ctx.admin, &lot_id, &250, &reason(&ctx));
+    assert_eq!(outcome.buffer_cancelled, 100);
+    assert_eq!(outcome.clawed_back, 150);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 0);
+
+    // 150 tons split over 600 and 200 outstanding: 112.5 and 37.5, remainder to the first lot
+    assert_credit_quantity(&ctx, &credit_id, 487);
+    assert_credit_quantity(&ctx, &lot_id, 163);
+
+    let lot_history = ctx.client.get_credit_history(&lot_id);
+    let event = lot_history.get(lot_history.len() - 1).unwrap();
+    assert_eq!(event.event_type, EventType::Reversal);
+    assert_eq!(event.from, Some(buyer));
+    assert_eq!(event.quantity, 37);
+
+    // Retired tonnage is untouched
+    let (_, _, _, total_retired) = ctx.client.get_contract_stats();
+    assert_eq!(total_retired, 100);
+}
+
+#[test]
+fn test_reversal_capped_at_outstanding_credits() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    retire_test_credit(&ctx, credit_id.clone(), &issuer, 400);
+
+    let outcome = ctx
+        .client
+        .reverse_credit(&ctx.admin, &credit_id, &1000, &reason(&ctx));
+    assert_eq!(outcome.buffer_cancelled, 0);
+    assert_eq!(outcome.clawed_back, 600);
+
+    assert_credit_quantity(&ctx, &credit_id, 0);
+    assert_credit_status(&ctx, &credit_id, CreditStatus::Reversed);
+}
+
+#[test]
+#[should_panic(expected = "Reversal exceeds issuance")]
+fn test_reversal_capped_at_issuance_size() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    // A large issuance funds the pool with 1_000 buffer credits
+    ctx.client.set_buffer_rate(&1_000);
+    issue_test_credit(&ctx, &issuer, 10_000);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 1_000);
+
+    // A 100 ton issuance cannot draw more than its own 100 tons from the pool
+    let small = issue_credit_with_hash(&ctx, &issuer, "Verra", 100, 9);
+    ctx.client
+        .reverse_credit(&ctx.admin, &small, &101, &reason(&ctx));
+}
+
+#[test]
+fn test_repeated_reversals_capped_at_issuance_size() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    ctx.client.set_buffer_rate(&1_000);
+    issue_test_credit(&ctx, &issuer, 10_000);
+    let small = issue_credit_with_hash(&ctx, &issuer, "Verra", 100, 9);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 1_010);
+
+    // Its 100 tons, withheld share included, can be reversed only once over
+    ctx.client
+        .reverse_credit(&ctx.admin, &small, &60, &reason(&ctx));
+    ctx.client
+        .reverse_credit(&ctx.admin, &small, &40, &reason(&ctx));
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 910);
+    assert!(ctx
+        .client
+        .try_reverse_credit(&ctx.admin, &small, &1, &reason(&ctx))
+        .is_err());
+}
+
+#[test]
+#[should_panic(expected = "Credit is reversed")]
+fn test_reversed_credit_cannot_trade() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    ctx.client
+        .reverse_credit(&ctx.admin, &credit_id, &1000, &reason(&ctx));
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 100);
+}
+
+#[test]
+fn test_verifier_can_record_reversal() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let verifier = Address::generate(&ctx.env);
+    ctx.client.register_verifier(&verifier);
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let outcome = ctx
+        .client
+        .reverse_credit(&verifier, &credit_id, &100, &reason(&ctx));
+    assert_eq!(outcome.clawed_back, 100);
+    assert_credit_quantity(&ctx, &credit_id, 900);
+}
+
+#[test]
+#[should_panic(expected = "Credit is awaiting verification")]
+fn test_pending_credit_cannot_draw_on_buffer() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    // Another issuance has funded the pool
+    ctx.client.set_buffer_rate(&1_000);
+    issue_test_credit(&ctx, &issuer, 1000);
+    assert_eq!(ctx.client.get_buffer_pool_balance(), 100);
+
+    // The pending issuance's own share never entered the pool
+    ctx.client.set_verifier_approval_required(&true);
+    let pending_id = issue_test_credit(&ctx, &issuer, 500);
+    assert_credit_status(&ctx, &pending_id, CreditStatus::PendingVerification);
+
+    ctx.client
+        .reverse_credit(&ctx.admin, &pending_id, &50, &reason(&ctx));
+}
+
+#[test]
+#[should_panic(expected = "Not authorized to record reversals")]
+fn test_reversal_requires_admin_or_verifier() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    ctx.client
+        .reverse_credit(&issuer, &credit_id, &100, &reason(&ctx));
+}
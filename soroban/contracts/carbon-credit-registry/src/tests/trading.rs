@@ -1,8 +1,14 @@
 #![cfg(test)]
 
-use soroban_sdk::{testutils::Address as _, Address};
+use soroban_sdk::{
+    testutils::{storage::Persistent as _, Address as _},
+    vec, Address, String,
+};
 
-use crate::CreditStatus;
+use crate::{
+    utils::{self, PERSISTENT_BUMP_AMOUNT},
+    CreditStatus, DataKey, TradingParams,
+};
 
 use super::utils::*;
 
@@ -58,10 +64,159 @@ fn test_trade_partial_credit() {
 
     // Trade partial quantity
     let buyer = Address::generate(&ctx.env);
-    trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 500);
+    let lot_id = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 500);
+
+    // Seller keeps the remainder, buyer holds a new child lot
+    assert_ne!(lot_id, credit_id);
+    assert_credit_owner(&ctx, &credit_id, &issuer);
+    assert_credit_quantity(&ctx, &credit_id, 500);
+    assert_credit_owner(&ctx, &lot_id, &buyer);
+    assert_credit_quantity(&ctx, &lot_id, 500);
+    assert_credit_status(&ctx, &lot_id, CreditStatus::Traded);
+}
 
-    // Verify ownership changed
-    assert_credit_owner(&ctx, &credit_id, &buyer);
+#[test]
+fn test_trade_small_lot_from_large_issuance() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 10_000);
+
+    let buyer1 = Address::generate(&ctx.env);
+    let buyer2 = Address::generate(&ctx.env);
+    let lot1 = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer1, 50);
+    let lot2 = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer2, 50);
+
+    assert_ne!(lot1, lot2);
+    assert_credit_quantity(&ctx, &credit_id, 9_900);
+    assert_credit_quantity(&ctx, &lot1, 50);
+    assert_credit_quantity(&ctx, &lot2, 50);
+
+    // Child lots keep the project data and point back at the issuance
+    let child = ctx.client.get_credit_status(&lot1).unwrap();
+    let parent = ctx.client.get_credit_status(&credit_id).unwrap();
+    assert_eq!(child.issuer, parent.issuer);
+    assert_eq!(child.vintage_year, parent.vintage_year);
+    assert_eq!(child.verification_hash, parent.verification_hash);
+    assert_eq!(child.lineage, vec![&ctx.env, credit_id.clone()]);
+
+    let children = ctx.client.get_child_credits(&credit_id);
+    assert_eq!(children, vec![&ctx.env, lot1.clone(), lot2.clone()]);
+
+    // Both lots show the trade in their history
+    assert_eq!(ctx.client.get_credit_history(&credit_id).len(), 3);
+    let history = ctx.client.get_credit_history(&lot1);
+    assert_eq!(history.len(), 1);
+
+    // Trade events record the agreed price and the token paid in
+    let metadata = history.get(0).unwrap().metadata;
+    assert_eq!(
+        metadata.get(String::from_str(&ctx.env, "price")),
+        Some(utils::i128_to_string(&ctx.env, TEST_PRICE))
+    );
+    assert_eq!(
+        metadata.get(String::from_str(&ctx.env, "payment_token")),
+        Some(ctx.payment_token.to_string())
+    );
+}
+
+#[test]
+fn test_child_lots_live_in_persistent_storage() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1_000);
+    let buyer = Address::generate(&ctx.env);
+    let lot = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer, 100);
+
+    ctx.env.as_contract(&ctx.client.address, || {
+        for key in [
+            DataKey::Credit(lot.clone()),
+            DataKey::ChildCredits(credit_id),
+        ] {
+            assert!(!ctx.env.storage().instance().has(&key));
+            assert_eq!(
+                ctx.env.storage().persistent().get_ttl(&key),
+                PERSISTENT_BUMP_AMOUNT
+            );
+        }
+    });
+}
+
+#[test]
+fn test_resplit_child_lot_extends_lineage() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 10_000);
+
+    let buyer1 = Address::generate(&ctx.env);
+    let buyer2 = Address::generate(&ctx.env);
+    let lot1 = trade_test_credit(&ctx, credit_id.clone(), &issuer, &buyer1, 50);
+    let lot2 = trade_test_credit(&ctx, lot1.clone(), &buyer1, &buyer2, 20);
+
+    assert_credit_quantity(&ctx, &lot1, 30);
+    assert_credit_owner(&ctx, &lot2, &buyer2);
+    let grandchild = ctx.client.get_credit_status(&lot2).unwrap();
+    assert_eq!(grandchild.lineage, vec![&ctx.env, credit_id, lot1]);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient credit quantity")]
+fn test_trade_more_than_held() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 1001);
+}
+
+#[test]
+#[should_panic(expected = "Quantity must be positive")]
+fn test_trade_zero_quantity() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 0);
+}
+
+#[test]
+#[should_panic(expected = "Caller does not own this credit")]
+fn test_trade_credit_not_owned() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let stranger = Address::generate(&ctx.env);
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &stranger, &buyer, 100);
+}
+
+#[test]
+#[should_panic(expected = "Caller does not own this credit")]
+fn test_seller_cannot_trade_split_lot() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    let lot_id = trade_test_credit(&ctx, credit_id, &issuer, &buyer, 100);
+
+    let other = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, lot_id, &issuer, &other, 100);
+}
+
+#[test]
+#[should_panic(expected = "Credit is suspended")]
+fn test_trade_suspended_credit() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+    ctx.client
+        .suspend_credit(&credit_id, &String::from_str(&ctx.env, "Audit"));
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 100);
 }
 
 #[test]
@@ -199,3 +354,111 @@ fn test_trade_different_verification_standards() {
     assert_credit_owner(&ctx, &verra_credit, &buyer);
     assert_credit_owner(&ctx, &gold_credit, &buyer);
 }
+
+// ============ PAYMENT SETTLEMENT TESTS ============
+
+#[test]
+fn test_trade_pays_seller_and_treasury() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 1000);
+
+    // 1000 tons at 50 = 50_000; 0.25% fee = 125 to the treasury (admin by default)
+    assert_eq!(token_balance(&ctx, &buyer), 0);
+    assert_eq!(token_balance(&ctx, &issuer), 49_875);
+    assert_eq!(token_balance(&ctx, &ctx.admin), 125);
+}
+
+#[test]
+fn test_trade_fee_goes_to_configured_treasury() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let treasury = Address::generate(&ctx.env);
+    ctx.client.set_treasury(&treasury);
+    assert_eq!(ctx.client.get_treasury(), treasury);
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 10_000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 400);
+
+    // 400 tons at 50 = 20_000; 0.25% fee = 50
+    assert_eq!(token_balance(&ctx, &issuer), 19_950);
+    assert_eq!(token_balance(&ctx, &treasury), 50);
+    assert_eq!(token_balance(&ctx, &ctx.admin), 0);
+}
+
+#[test]
+fn test_trade_requires_buyer_and_seller_auth() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    trade_test_credit(&ctx, credit_id, &issuer, &buyer, 100);
+
+    let auths = ctx.env.auths();
+    assert!(auths.iter().any(|(signer, _)| *signer == issuer));
+    assert!(auths.iter().any(|(signer, _)| *signer == buyer));
+}
+
+#[test]
+fn test_unfunded_buyer_leaves_credit_untouched() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    let params = TradingParams {
+        credit_id: credit_id.clone(),
+        from: issuer.clone(),
+        to: buyer.clone(),
+        quantity: 100,
+        price: TEST_PRICE,
+        payment_token: ctx.payment_token.clone(),
+    };
+    assert!(ctx.client.try_trade_credit(&params).is_err());
+
+    assert_credit_owner(&ctx, &credit_id, &issuer);
+    assert_credit_quantity(&ctx, &credit_id, 1000);
+    assert_eq!(ctx.client.get_child_credits(&credit_id).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Price must be positive")]
+fn test_trade_zero_price() {
+    let (ctx, issuer) = setup_with_issuer();
+
+    let credit_id = issue_test_credit(&ctx, &issuer, 1000);
+
+    let buyer = Address::generate(&ctx.env);
+    let params = TradingParams {
+        credit_id,
+        from: issuer,
+        to: buyer,
+        quantity: 100,
+        price: 0,
+        payment_token: ctx.payment_token.clone(),
+    };
+    ctx.client.trade_credit(&params);
+}
+
+#[test]
+fn test_i128_to_string() {
+    let env = soroban_sdk::Env::default();
+    for (value, expected) in [
+        (0, "0"),
+        (50, "50"),
+        (-7, "-7"),
+        (i128::MAX, "170141183460469231731687303715884105727"),
+        (i128::MIN, "-170141183460469231731687303715884105728"),
+    ] {
+        assert_eq!(
+            utils::i128_to_string(&env, value),
+            String::from_str(&env, expected)
+        );
+    }
+}
@@ -2,19 +2,25 @@
 
 use soroban_sdk::{
     testutils::{Address as _, Ledger as _},
-    Address, BytesN, Env, Map, String, Vec,
+    token, Address, BytesN, Env, Map, String, Vec,
 };
 
 use crate::{
-    CarbonCreditRegistry, CarbonCreditRegistryClient, CreditStatus, RetirementParams, TradingParams,
+    Beneficiary, CarbonCreditRegistry, CarbonCreditRegistryClient, CreditStatus, RetirementParams,
+    TradingParams,
 };
 
 // ============ TEST CONTEXT ============
 
+/// Price per ton used by `trade_test_credit`
+pub const TEST_PRICE: i128 = 50;
+
 pub struct TestContext {
     pub env: Env,
     pub client: CarbonCreditRegistryClient<'static>,
+    #[allow(dead_code)]
     pub admin: Address,
+    pub payment_token: Address,
 }
 
 // ============ SETUP FUNCTIONS ============
@@ -31,7 +37,16 @@ pub fn setup_test_env() -> TestContext {
 
     client.initialize(&admin, &25, &10); // 0.25% trading fee, 0.10% retirement fee
 
-    TestContext { env, client, admin }
+    let payment_token = env
+        .register_stellar_asset_contract_v2(admin.clone())
+        .address();
+
+    TestContext {
+        env,
+        client,
+        admin,
+        payment_token,
+    }
 }
 
 /// Creates test environment with registered issuer
@@ -129,7 +144,41 @@ pub fn issue_credit_with_params(
     credit_id
 }
 
-/// Trades a credit from one owner to another
+/// Issues a credit under `standard` against a verification hash filled with `hash_seed`
+#[allow(dead_code)]
+pub fn issue_credit_with_hash(
+    ctx: &TestContext,
+    issuer: &Address,
+    standard: &str,
+    quantity: i128,
+    hash_seed: u8,
+) -> BytesN<32> {
+    ctx.client.issue_credit(
+        issuer,
+        &String::from_str(&ctx.env, "Reforestation"),
+        &String::from_str(&ctx.env, "Brazil"),
+        &String::from_str(&ctx.env, standard),
+        &2024,
+        &quantity,
+        &BytesN::from_array(&ctx.env, &[hash_seed; 32]),
+        &Map::new(&ctx.env),
+    )
+}
+
+/// Mints payment tokens to an account
+#[allow(dead_code)]
+pub fn fund_account(ctx: &TestContext, to: &Address, amount: i128) {
+    token::StellarAssetClient::new(&ctx.env, &ctx.payment_token).mint(to, &amount);
+}
+
+/// Returns an account's payment token balance
+#[allow(dead_code)]
+pub fn token_balance(ctx: &TestContext, of: &Address) -> i128 {
+    token::Client::new(&ctx.env, &ctx.payment_token).balance(of)
+}
+
+/// Trades a credit from one owner to another at `TEST_PRICE`, funding the
+/// buyer first. Returns the buyer's lot id
 #[allow(dead_code)]
 pub fn trade_test_credit(
     ctx: &TestContext,
@@ -137,29 +186,47 @@ pub fn trade_test_credit(
     from: &Address,
     to: &Address,
     quantity: i128,
-) {
-    let payment_token = Address::generate(&ctx.env);
+) -> BytesN<32> {
+    fund_account(ctx, to, quantity * TEST_PRICE);
 
     let params = TradingParams {
         credit_id,
         from: from.clone(),
         to: to.clone(),
         quantity,
-        price: 50,
-        payment_token,
+        price: TEST_PRICE,
+        payment_token: ctx.payment_token.clone(),
     };
 
-    ctx.client.trade_credit(&params);
+    ctx.client.trade_credit(&params)
 }
 
-/// Retires a credit
+/// Retires a credit on the owner's own behalf, returning the record id
 #[allow(dead_code)]
 pub fn retire_test_credit(
     ctx: &TestContext,
     credit_id: BytesN<32>,
     owner: &Address,
     quantity: i128,
-) {
+) -> u64 {
+    retire_for_beneficiary(
+        ctx,
+        credit_id,
+        owner,
+        quantity,
+        Beneficiary::Account(owner.clone()),
+    )
+}
+
+/// Retires a credit on behalf of a beneficiary, returning the record id
+#[allow(dead_code)]
+pub fn retire_for_beneficiary(
+    ctx: &TestContext,
+    credit_id: BytesN<32>,
+    owner: &Address,
+    quantity: i128,
+    beneficiary: Beneficiary,
+) -> u64 {
     let retirement_reason = String::from_str(&ctx.env, "Carbon offset claim");
     let retirement_certificate = BytesN::from_array(&ctx.env, &[3u8; 32]);
 
@@ -167,11 +234,13 @@ pub fn retire_test_credit(
         credit_id,
         owner: owner.clone(),
         quantity,
+        beneficiary,
+        reporting_period: String::from_str(&ctx.env, "FY2024"),
         retirement_reason,
         retirement_certificate,
     };
 
-    ctx.client.retire_credit(&params);
+    ctx.client.retire_credit(&params)
 }
 
 // ============ ASSERTION HELPERS ============
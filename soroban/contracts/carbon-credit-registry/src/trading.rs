@@ -1,44 +1,86 @@
-use soroban_sdk::{Bytes, BytesN, Env, Map, String, Vec};
+use soroban_sdk::{token, Address, BytesN, Env, Map, String, Vec};
 
 use crate::{
-    CarbonCredit, CreditEvent, CreditStatus, DataKey, EventType, RetirementParams, TradingParams,
+    retirement, utils, CarbonCredit, CreditEvent, CreditStatus, DataKey, EventType, IssuerProfile,
+    RetirementParams, TradingParams,
 };
 
-/// Trade a carbon credit from one owner to another
-pub fn trade_credit(env: Env, params: TradingParams) {
-    // Validate that the sender is authorized
-    params.from.require_auth();
+/// Basis points denominator for fee rates
+const BASIS_POINTS: i128 = 10_000;
 
-    // Get the credit
-    let mut credit: CarbonCredit = env
-        .storage()
-        .instance()
-        .get(&DataKey::Credit(params.credit_id.clone()))
-        .unwrap();
+/// Trade a carbon credit from one owner to another.
+///
+/// The buyer pays `quantity * price` in `payment_token` within the same
+/// invocation: the seller receives the proceeds less the trading fee, which
+/// goes to the registry treasury. Returns the id of the lot now held by the
+/// buyer (see `transfer_lot`).
+pub fn trade_credit(env: Env, params: TradingParams) -> BytesN<32> {
+    // Both sides authorize: the seller delivers credits, the buyer pays
+    params.from.require_auth();
+    params.to.require_auth();
 
-    // Update credit ownership
-    credit.current_owner = params.to.clone();
-    credit.status = CreditStatus::Traded;
+    if params.from == params.to {
+        panic!("Cannot trade a credit to its current owner");
+    }
 
-    env.storage()
-        .instance()
-        .set(&DataKey::Credit(params.credit_id.clone()), &credit);
+    let lot_id = transfer_lot(
+        &env,
+        &params.credit_id,
+        &params.from,
+        &params.to,
+        params.quantity,
+    );
+    settle_payment(&env, &params);
 
     // Record trade event
-    record_trade_event(&env, &params);
+    record_trade_event(&env, &params.credit_id, &params);
+    if lot_id != params.credit_id {
+        record_trade_event(&env, &lot_id, &params);
+    }
+
+    lot_id
 }
 
-/// Retire a carbon credit for carbon offset claims
-pub fn retire_credit(env: Env, params: RetirementParams) {
+/// Move `quantity` tons of a lot held by `from` to `to`.
+///
+/// Moving the lot's whole remaining quantity transfers ownership in place.
+/// A smaller quantity is split off into a child lot owned by `to`, which
+/// carries the parent's project data and lineage. Returns the id of the lot
+/// now held by `to`. Callers handle authorization and events.
+pub fn transfer_lot(
+    env: &Env,
+    credit_id: &BytesN<32>,
+    from: &Address,
+    to: &Address,
+    quantity: i128,
+) -> BytesN<32> {
+    let mut credit = load_owned_credit(env, credit_id, from);
+    check_quantity(&credit, quantity);
+
+    let lot_id = if quantity == credit.quantity {
+        // Whole lot changes hands
+        credit.current_owner = to.clone();
+        credit.status = CreditStatus::Traded;
+        credit_id.clone()
+    } else {
+        // Split the quantity off into a child lot
+        credit.quantity -= quantity;
+        split_credit(env, &credit, to, quantity)
+    };
+
+    utils::set_persistent(env, &DataKey::Credit(credit_id.clone()), &credit);
+
+    lot_id
+}
+
+/// Retire a carbon credit for carbon offset claims, returning the id of the
+/// retirement record
+pub fn retire_credit(env: Env, params: RetirementParams) -> u64 {
     // Validate that the owner is authorized
     params.owner.require_auth();
 
-    // Get the credit
-    let mut credit: CarbonCredit = env
-        .storage()
-        .instance()
-        .get(&DataKey::Credit(params.credit_id.clone()))
-        .unwrap();
+    let mut credit = load_owned_credit(&env, &params.credit_id, &params.owner);
+    check_quantity(&credit, params.quantity);
 
     // Update credit status
     credit.quantity -= params.quantity;
@@ -46,15 +88,112 @@ pub fn retire_credit(env: Env, params: RetirementParams) {
         credit.status = CreditStatus::Retired;
     }
 
-    env.storage()
-        .instance()
-        .set(&DataKey::Credit(params.credit_id.clone()), &credit);
+    utils::set_persistent(&env, &DataKey::Credit(params.credit_id.clone()), &credit);
 
     // Update contract statistics
-    update_retirement_stats(&env, params.quantity);
+    update_retirement_stats(&env, &credit.issuer, params.quantity);
 
     // Record retirement event
     record_retirement_event(&env, &params);
+    retirement::record_retirement(&env, &credit, &params)
+}
+
+/// Get the lots split off from a credit, in creation order
+pub fn get_child_credits(env: &Env, credit_id: BytesN<32>) -> Vec<BytesN<32>> {
+    utils::get_persistent(env, &DataKey::ChildCredits(credit_id)).unwrap_or(Vec::new(env))
+}
+
+/// Get the address that collects trading fees, defaulting to the admin
+pub fn get_treasury(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Treasury)
+        .unwrap_or_else(|| env.storage().instance().get(&DataKey::Admin).unwrap())
+}
+
+/// Move the buyer's payment to the seller and the trading fee to the treasury
+fn settle_payment(env: &Env, params: &TradingParams) {
+    if params.price <= 0 {
+        panic!("Price must be positive");
+    }
+
+    let total = params
+        .quantity
+        .checked_mul(params.price)
+        .unwrap_or_else(|| panic!("Trade value overflow"));
+    let fee_rate: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TradingFeeRate)
+        .unwrap_or(0);
+    let fee = total * fee_rate as i128 / BASIS_POINTS;
+
+    let token_client = token::Client::new(env, &params.payment_token);
+    token_client.transfer(&params.to, &params.from, &(total - fee));
+    if fee > 0 {
+        token_client.transfer(&params.to, &get_treasury(env), &fee);
+    }
+}
+
+/// Load a credit and check that `owner` holds it and it can still move
+pub fn load_owned_credit(env: &Env, credit_id: &BytesN<32>, owner: &Address) -> CarbonCredit {
+    let credit: CarbonCredit = utils::get_persistent(env, &DataKey::Credit(credit_id.clone()))
+        .unwrap_or_else(|| panic!("Credit not found"));
+
+    if credit.current_owner != *owner {
+        panic!("Caller does not own this credit");
+    }
+    match credit.status {
+        CreditStatus::Retired => panic!("Credit is retired"),
+        CreditStatus::Suspended => panic!("Credit is suspended"),
+        CreditStatus::PendingVerification => panic!("Credit is awaiting verification"),
+        CreditStatus::Reversed => panic!("Credit is reversed"),
+        _ => {}
+    }
+
+    credit
+}
+
+/// Validate a trade or retirement quantity against the lot's remaining tons
+fn check_quantity(credit: &CarbonCredit, quantity: i128) {
+    if quantity <= 0 {
+        panic!("Quantity must be positive");
+    }
+    if quantity > credit.quantity {
+        panic!("Insufficient credit quantity");
+    }
+}
+
+/// Create a child lot of `quantity` tons owned by `owner`
+fn split_credit(env: &Env, parent: &CarbonCredit, owner: &Address, quantity: i128) -> BytesN<32> {
+    let mut children = get_child_credits(env, parent.id.clone());
+    let child_id = utils::generate_child_credit_id(env, &parent.id, children.len());
+
+    let mut lineage = parent.lineage.clone();
+    lineage.push_back(parent.id.clone());
+
+    let child = CarbonCredit {
+        id: child_id.clone(),
+        issuer: parent.issuer.clone(),
+        project_type: parent.project_type.clone(),
+        project_location: parent.project_location.clone(),
+        verification_standard: parent.verification_standard.clone(),
+        issuance_date: parent.issuance_date,
+        vintage_year: parent.vintage_year,
+        quantity,
+        current_owner: owner.clone(),
+        status: CreditStatus::Traded,
+        verification_hash: parent.verification_hash.clone(),
+        metadata: parent.metadata.clone(),
+        lineage,
+    };
+
+    utils::set_persistent(env, &DataKey::Credit(child_id.clone()), &child);
+
+    children.push_back(child_id.clone());
+    utils::set_persistent(env, &DataKey::ChildCredits(parent.id.clone()), &children);
+
+    child_id
 }
 
 /// Get trading history for a credit
@@ -85,23 +224,26 @@ pub fn get_trading_history(env: Env, credit_id: BytesN<32>) -> Vec<CreditEvent>
 }
 
 /// Record a trade event
-fn record_trade_event(env: &Env, params: &TradingParams) {
+fn record_trade_event(env: &Env, credit_id: &BytesN<32>, params: &TradingParams) {
     let event_count: u32 = env
         .storage()
         .instance()
-        .get(&DataKey::EventCount(params.credit_id.clone()))
+        .get(&DataKey::EventCount(credit_id.clone()))
         .unwrap_or(0);
 
     let mut metadata = Map::new(env);
-    metadata.set(String::from_str(env, "price"), String::from_str(env, "50")); // Simplified for now
+    metadata.set(
+        String::from_str(env, "price"),
+        utils::i128_to_string(env, params.price),
+    );
     metadata.set(
         String::from_str(env, "payment_token"),
-        String::from_str(env, "token"),
+        params.payment_token.to_string(),
     );
 
     let event = CreditEvent {
         event_type: EventType::Trade,
-        credit_id: params.credit_id.clone(),
+        credit_id: credit_id.clone(),
         timestamp: env.ledger().timestamp(),
         from: Some(params.from.clone()),
         to: Some(params.to.clone()),
@@ -111,13 +253,12 @@ fn record_trade_event(env: &Env, params: &TradingParams) {
     };
 
     env.storage().instance().set(
-        &DataKey::CreditEvent(params.credit_id.clone(), event_count),
+        &DataKey::CreditEvent(credit_id.clone(), event_count),
         &event,
     );
-    env.storage().instance().set(
-        &DataKey::EventCount(params.credit_id.clone()),
-        &(event_count + 1),
-    );
+    env.storage()
+        .instance()
+        .set(&DataKey::EventCount(credit_id.clone()), &(event_count + 1));
 }
 
 /// Record a retirement event
@@ -135,7 +276,7 @@ fn record_retirement_event(env: &Env, params: &RetirementParams) {
     );
     metadata.set(
         String::from_str(env, "certificate"),
-        String::from_str(env, "cert"),
+        utils::bytes_to_hex(env, &params.retirement_certificate),
     );
 
     let event = CreditEvent {
@@ -160,7 +301,7 @@ fn record_retirement_event(env: &Env, params: &RetirementParams) {
 }
 
 /// Update retirement statistics
-fn update_retirement_stats(env: &Env, retired_quantity: i128) {
+fn update_retirement_stats(env: &Env, issuer: &Address, retired_quantity: i128) {
     let mut total_retired: i128 = env
         .storage()
         .instance()
@@ -171,4 +312,14 @@ fn update_retirement_stats(env: &Env, retired_quantity: i128) {
     env.storage()
         .instance()
         .set(&DataKey::TotalCreditsRetired, &total_retired);
+
+    let issuer_key = DataKey::Issuer(issuer.clone());
+    if let Some(mut profile) = env
+        .storage()
+        .instance()
+        .get::<DataKey, IssuerProfile>(&issuer_key)
+    {
+        profile.total_retired += retired_quantity;
+        env.storage().instance().set(&issuer_key, &profile);
+    }
 }
@@ -1,20 +1,80 @@
-use soroban_sdk::{Address, Bytes, BytesN, Env};
-
-/// Generate a unique credit ID based on issuer, verification hash, and timestamp
-pub fn generate_credit_id(
-    env: &Env,
-    _issuer: &Address,
-    verification_hash: &BytesN<32>,
-) -> BytesN<32> {
-    // Simplified approach - use timestamp and verification hash
-    let timestamp = env.ledger().timestamp();
-    let timestamp_bytes = timestamp.to_be_bytes();
-
-    // Create a simple hash from timestamp and verification hash
-    let mut combined = [0u8; 40];
-    combined[0..8].copy_from_slice(&timestamp_bytes);
-    combined[8..40].copy_from_slice(&verification_hash.to_array());
+use soroban_sdk::{Bytes, BytesN, Env, IntoVal, String, TryFromVal, Val};
+
+use crate::DataKey;
+
+const DAY_IN_LEDGERS: u32 = 17_280;
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 120 * DAY_IN_LEDGERS;
+const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Generate a credit ID from the verification hash. Each hash is issued at
+/// most once, so the ID is stable and unique
+pub fn generate_credit_id(env: &Env, verification_hash: &BytesN<32>) -> BytesN<32> {
+    let bytes_data = Bytes::from_slice(env, &verification_hash.to_array());
+    env.crypto().sha256(&bytes_data).into()
+}
+
+/// Derive the id of a lot split off from `parent_id`, from its position among
+/// the parent's children
+pub fn generate_child_credit_id(env: &Env, parent_id: &BytesN<32>, index: u32) -> BytesN<32> {
+    let mut combined = [0u8; 36];
+    combined[0..32].copy_from_slice(&parent_id.to_array());
+    combined[32..36].copy_from_slice(&index.to_be_bytes());
 
     let bytes_data = Bytes::from_slice(env, &combined);
     env.crypto().sha256(&bytes_data).into()
 }
+
+/// Format an integer as its decimal string
+pub fn i128_to_string(env: &Env, value: i128) -> String {
+    // 39 digits plus a sign cover every i128
+    let mut digits = [0u8; 40];
+    let mut start = digits.len();
+    let mut remaining = value.unsigned_abs();
+    loop {
+        start -= 1;
+        digits[start] = b'0' + (remaining % 10) as u8;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+    if value < 0 {
+        start -= 1;
+        digits[start] = b'-';
+    }
+    String::from_bytes(env, &digits[start..])
+}
+
+/// Format a hash as lowercase hex
+pub fn bytes_to_hex(env: &Env, bytes: &BytesN<32>) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut digits = [0u8; 64];
+    for (i, byte) in bytes.to_array().iter().enumerate() {
+        digits[2 * i] = HEX[(byte >> 4) as usize];
+        digits[2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    String::from_bytes(env, &digits)
+}
+
+/// Read a persistent entry, extending its TTL when present
+pub fn get_persistent<V: TryFromVal<Env, Val>>(env: &Env, key: &DataKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+    value
+}
+
+/// Write a persistent entry and extend its TTL
+pub fn set_persistent<V: IntoVal<Env, Val>>(env: &Env, key: &DataKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
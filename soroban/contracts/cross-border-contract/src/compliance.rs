@@ -1,40 +1,239 @@
+use crate::liquidity;
 use crate::types::*;
-use soroban_sdk::{Address, BytesN, Env, Symbol};
+use soroban_sdk::{Address, BytesN, Env, Symbol, Vec};
 
-pub fn verify_compliance(env: Env, user: Address, documents: BytesN<32>) {
-    user.require_auth();
+pub fn add_compliance_officer(env: Env, officer: Address, organization: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
 
-    // Simulate compliance check (integrate with external KYC/AML service)
-    let compliance = ComplianceData {
-        kyc_verified: true,
-        aml_verified: true,
-        verification_documents: documents.clone(),
-    };
+    env.storage()
+        .instance()
+        .set(&DataKey::ComplianceOfficer(officer.clone()), &organization);
+
+    env.events().publish(
+        (Symbol::new(&env, "ComplianceOfficerAdded"),),
+        (officer, organization),
+    );
+}
+
+pub fn remove_compliance_officer(env: Env, officer: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
 
     env.storage()
         .instance()
-        .set(&DataKey::Compliance(user.clone()), &compliance);
+        .remove(&DataKey::ComplianceOfficer(officer.clone()));
+
+    env.events()
+        .publish((Symbol::new(&env, "ComplianceOfficerRemoved"),), officer);
+}
+
+pub fn set_tier_limits(env: Env, tier: RiskTier, limits: TierLimits) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if limits.max_transfer < 0 || limits.daily_limit < 0 || limits.monthly_limit < 0 {
+        panic!("Limits cannot be negative");
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::TierLimits(tier.clone()), &limits);
 
     env.events().publish(
-        (Symbol::new(&env, "ComplianceVerified"),),
+        (Symbol::new(&env, "TierLimitsUpdated"),),
         (
-            user,
-            compliance.kyc_verified,
-            compliance.aml_verified,
-            documents,
+            tier,
+            limits.max_transfer,
+            limits.daily_limit,
+            limits.monthly_limit,
         ),
     );
 }
 
+pub fn approve_user(
+    env: Env,
+    officer: Address,
+    user: Address,
+    documents: BytesN<32>,
+    identity_hash: BytesN<32>,
+    risk_tier: RiskTier,
+    expires_at: u64,
+) {
+    let organization = require_officer(&env, &officer);
+    check_review_rights(&env, &officer, &organization, &user);
+    if expires_at <= env.ledger().timestamp() {
+        panic!("Expiry must be in the future");
+    }
+
+    let compliance = ComplianceData {
+        status: ComplianceStatus::Approved,
+        risk_tier,
+        verification_documents: documents,
+        identity_hash,
+        officer,
+        organization,
+        reviewed_at: env.ledger().timestamp(),
+        expires_at,
+    };
+    store_review(&env, user, compliance);
+}
+
+pub fn reject_user(
+    env: Env,
+    officer: Address,
+    user: Address,
+    documents: BytesN<32>,
+    identity_hash: BytesN<32>,
+) {
+    let organization = require_officer(&env, &officer);
+    check_review_rights(&env, &officer, &organization, &user);
+
+    let compliance = ComplianceData {
+        status: ComplianceStatus::Rejected,
+        risk_tier: RiskTier::High,
+        verification_documents: documents,
+        identity_hash,
+        officer,
+        organization,
+        reviewed_at: env.ledger().timestamp(),
+        expires_at: 0,
+    };
+    store_review(&env, user, compliance);
+}
+
+pub fn get_compliance(env: Env, user: Address) -> ComplianceData {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Compliance(user))
+        .unwrap_or_else(|| panic!("Compliance record not found"))
+}
+
 pub fn is_compliant(env: &Env, user: &Address) -> bool {
+    let compliance: Option<ComplianceData> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Compliance(user.clone()));
+    match compliance {
+        Some(data) => {
+            data.status == ComplianceStatus::Approved && env.ledger().timestamp() < data.expires_at
+        }
+        None => false,
+    }
+}
+
+// Checks the sender's tier limits against the rolling 24h and 30d settled volume
+pub fn check_transfer_limits(
+    env: &Env,
+    sender: &Address,
+    amount: i128,
+) -> Result<(), &'static str> {
     let compliance: ComplianceData = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Compliance(sender.clone()))
+        .ok_or("Compliance record not found")?;
+    let limits: TierLimits = env
         .storage()
         .instance()
-        .get(&DataKey::Compliance(user.clone()))
-        .unwrap_or(ComplianceData {
-            kyc_verified: false,
-            aml_verified: false,
-            verification_documents: BytesN::from_array(&env, &[0; 32]),
-        });
-    compliance.kyc_verified && compliance.aml_verified
+        .get(&DataKey::TierLimits(compliance.risk_tier))
+        .ok_or("Transfer limits not configured")?;
+
+    if amount > limits.max_transfer {
+        return Err("Transfer exceeds per-transfer limit");
+    }
+
+    let now = env.ledger().timestamp();
+    let mut daily_volume = 0i128;
+    for entry in volume_log(env, sender).iter() {
+        if now - entry.timestamp < DAY_SECONDS {
+            daily_volume += entry.amount;
+        }
+    }
+
+    if daily_volume + amount > limits.daily_limit {
+        return Err("Daily transfer limit exceeded");
+    }
+    if monthly_volume(env, sender) + amount > limits.monthly_limit {
+        return Err("Monthly transfer limit exceeded");
+    }
+    Ok(())
+}
+
+// Entries older than the monthly window no longer count and are dropped
+pub fn record_volume(env: &Env, sender: &Address, amount: i128) {
+    let now = env.ledger().timestamp();
+    let mut retained = Vec::new(env);
+    for entry in volume_log(env, sender).iter() {
+        if now - entry.timestamp < MONTH_SECONDS {
+            retained.push_back(entry);
+        }
+    }
+    retained.push_back(VolumeEntry {
+        timestamp: now,
+        amount,
+    });
+    env.storage()
+        .persistent()
+        .set(&DataKey::VolumeLog(sender.clone()), &retained);
+}
+
+// Sum of the sender's settled volume within the rolling monthly window
+pub fn monthly_volume(env: &Env, sender: &Address) -> i128 {
+    let now = env.ledger().timestamp();
+    let mut volume = 0i128;
+    for entry in volume_log(env, sender).iter() {
+        if now - entry.timestamp < MONTH_SECONDS {
+            volume += entry.amount;
+        }
+    }
+    volume
+}
+
+fn volume_log(env: &Env, sender: &Address) -> Vec<VolumeEntry> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::VolumeLog(sender.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+fn require_officer(env: &Env, officer: &Address) -> Address {
+    officer.require_auth();
+    env.storage()
+        .instance()
+        .get(&DataKey::ComplianceOfficer(officer.clone()))
+        .unwrap_or_else(|| panic!("Not a compliance officer"))
+}
+
+// Officers may not review themselves, and a user's record belongs to the organization that first reviewed them
+fn check_review_rights(env: &Env, officer: &Address, organization: &Address, user: &Address) {
+    if officer == user {
+        panic!("Officers cannot review themselves");
+    }
+    let existing: Option<ComplianceData> = env
+        .storage()
+        .persistent()
+        .get(&DataKey::Compliance(user.clone()));
+    if let Some(existing) = existing {
+        if existing.organization != *organization {
+            panic!("User is reviewed by another organization");
+        }
+    }
+}
+
+fn store_review(env: &Env, user: Address, compliance: ComplianceData) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Compliance(user.clone()), &compliance);
+
+    env.events().publish(
+        (Symbol::new(env, "ComplianceReviewed"),),
+        (
+            user,
+            compliance.status,
+            compliance.risk_tier,
+            compliance.officer,
+            compliance.verification_documents,
+            compliance.expires_at,
+        ),
+    );
 }
@@ -1,5 +1,7 @@
+use crate::compliance;
+use crate::liquidity;
 use crate::types::*;
-use soroban_sdk::Env;
+use soroban_sdk::{Address, Env, String, Symbol};
 
 pub fn calculate_fees(env: Env, amount: i128, is_urgent: bool) -> i128 {
     let fee_structure: FeeStructure =
@@ -22,3 +24,226 @@ pub fn calculate_fees(env: Env, amount: i128, is_urgent: bool) -> i128 {
 
     fee
 }
+
+// Prices a transfer from its corridor schedule, falling back to the default fee structure
+pub fn quote_fee(env: Env, sender: Address, params: TransferParams) -> i128 {
+    let key = corridor_key(&params);
+    let schedule: FeeSchedule = match env.storage().instance().get(&key.0) {
+        Some(schedule) => schedule,
+        None => return calculate_fees(env, params.amount, params.is_urgent),
+    };
+
+    let now = env.ledger().timestamp();
+    let promotion: Option<FeePromotion> = env
+        .storage()
+        .instance()
+        .get(&key.1)
+        .filter(|promotion: &FeePromotion| promotion.starts_at <= now && now < promotion.ends_at);
+
+    let (base_fee, percentage) = match &promotion {
+        Some(promotion) => (promotion.base_fee, promotion.percentage),
+        None => {
+            let volume = compliance::monthly_volume(&env, &sender) + params.amount;
+            (schedule.base_fee, tier_percentage(&schedule, volume))
+        }
+    };
+
+    let mut fee = base_fee + params.amount * percentage as i128 / 10_000;
+    if params.is_urgent {
+        fee = fee * schedule.urgency_multiplier as i128 / 100;
+    }
+
+    // Promotions may undercut the corridor minimum but never exceed its maximum
+    if promotion.is_none() {
+        fee = fee.max(schedule.min_fee);
+    }
+    fee.min(schedule.max_fee)
+}
+
+pub fn set_fee_schedule(
+    env: Env,
+    source_currency: String,
+    target_currency: String,
+    destination_network: String,
+    schedule: FeeSchedule,
+) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if schedule.base_fee < 0 || schedule.min_fee < 0 || schedule.max_fee < schedule.min_fee {
+        panic!("Invalid fee caps");
+    }
+    if schedule.tiers.is_empty() || schedule.tiers.get(0).unwrap().min_volume != 0 {
+        panic!("Fee tiers must start at zero volume");
+    }
+    let mut previous: Option<i128> = None;
+    for tier in schedule.tiers.iter() {
+        if tier.percentage > 10_000 {
+            panic!("Fee percentage cannot exceed 100%");
+        }
+        if previous.is_some_and(|min_volume| tier.min_volume <= min_volume) {
+            panic!("Fee tiers must be in ascending volume order");
+        }
+        previous = Some(tier.min_volume);
+    }
+
+    env.storage().instance().set(
+        &DataKey::FeeSchedule(
+            source_currency.clone(),
+            target_currency.clone(),
+            destination_network.clone(),
+        ),
+        &schedule,
+    );
+
+    env.events().publish(
+        (Symbol::new(&env, "FeeScheduleUpdated"),),
+        (
+            source_currency,
+            target_currency,
+            destination_network,
+            schedule.base_fee,
+            schedule.min_fee,
+            schedule.max_fee,
+        ),
+    );
+}
+
+pub fn remove_fee_schedule(
+    env: Env,
+    source_currency: String,
+    target_currency: String,
+    destination_network: String,
+) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage().instance().remove(&DataKey::FeeSchedule(
+        source_currency.clone(),
+        target_currency.clone(),
+        destination_network.clone(),
+    ));
+
+    env.events().publish(
+        (Symbol::new(&env, "FeeScheduleRemoved"),),
+        (source_currency, target_currency, destination_network),
+    );
+}
+
+pub fn get_fee_schedule(
+    env: Env,
+    source_currency: String,
+    target_currency: String,
+    destination_network: String,
+) -> FeeSchedule {
+    env.storage()
+        .instance()
+        .get(&DataKey::FeeSchedule(
+            source_currency,
+            target_currency,
+            destination_network,
+        ))
+        .unwrap_or_else(|| panic!("Fee schedule not found"))
+}
+
+pub fn set_fee_promotion(
+    env: Env,
+    source_currency: String,
+    target_currency: String,
+    destination_network: String,
+    promotion: FeePromotion,
+) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if promotion.base_fee < 0 || promotion.percentage > 10_000 {
+        panic!("Invalid promotional fee");
+    }
+    if promotion.ends_at <= promotion.starts_at {
+        panic!("Promotion must end after it starts");
+    }
+
+    env.storage().instance().set(
+        &DataKey::FeePromotion(
+            source_currency.clone(),
+            target_currency.clone(),
+            destination_network.clone(),
+        ),
+        &promotion,
+    );
+
+    env.events().publish(
+        (Symbol::new(&env, "FeePromotionSet"),),
+        (
+            source_currency,
+            target_currency,
+            destination_network,
+            promotion.starts_at,
+            promotion.ends_at,
+        ),
+    );
+}
+
+pub fn remove_fee_promotion(
+    env: Env,
+    source_currency: String,
+    target_currency: String,
+    destination_network: String,
+) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage().instance().remove(&DataKey::FeePromotion(
+        source_currency.clone(),
+        target_currency.clone(),
+        destination_network.clone(),
+    ));
+
+    env.events().publish(
+        (Symbol::new(&env, "FeePromotionRemoved"),),
+        (source_currency, target_currency, destination_network),
+    );
+}
+
+pub fn set_treasury(env: Env, treasury: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage().instance().set(&DataKey::Treasury, &treasury);
+
+    env.events()
+        .publish((Symbol::new(&env, "TreasurySet"),), treasury);
+}
+
+pub fn get_treasury(env: &Env) -> Result<Address, ContractError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Treasury)
+        .ok_or(ContractError::TreasuryNotConfigured)
+}
+
+fn corridor_key(params: &TransferParams) -> (DataKey, DataKey) {
+    (
+        DataKey::FeeSchedule(
+            params.currency.clone(),
+            params.target_currency.clone(),
+            params.destination_network.clone(),
+        ),
+        DataKey::FeePromotion(
+            params.currency.clone(),
+            params.target_currency.clone(),
+            params.destination_network.clone(),
+        ),
+    )
+}
+
+// Highest tier whose volume threshold has been reached
+fn tier_percentage(schedule: &FeeSchedule, volume: i128) -> u32 {
+    let mut percentage = 0;
+    for tier in schedule.tiers.iter() {
+        if volume >= tier.min_volume {
+            percentage = tier.percentage;
+        }
+    }
+    percentage
+}
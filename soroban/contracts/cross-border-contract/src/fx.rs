@@ -1,13 +1,97 @@
+use crate::liquidity;
 use crate::types::*;
-use soroban_sdk::{Env, String, Symbol};
+use soroban_sdk::{Address, Env, String, Symbol};
+
+pub fn add_rate_publisher(env: Env, publisher: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage()
+        .instance()
+        .set(&DataKey::RatePublisher(publisher.clone()), &true);
+
+    env.events()
+        .publish((Symbol::new(&env, "RatePublisherAdded"),), publisher);
+}
+
+pub fn remove_rate_publisher(env: Env, publisher: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage()
+        .instance()
+        .remove(&DataKey::RatePublisher(publisher.clone()));
+
+    env.events()
+        .publish((Symbol::new(&env, "RatePublisherRemoved"),), publisher);
+}
+
+pub fn is_rate_publisher(env: &Env, publisher: &Address) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::RatePublisher(publisher.clone()))
+        .unwrap_or(false)
+}
+
+pub fn set_max_rate_age(env: Env, max_age: u64) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if max_age == 0 {
+        panic!("Max rate age must be positive");
+    }
+    env.storage().instance().set(&DataKey::MaxRateAge, &max_age);
+
+    env.events()
+        .publish((Symbol::new(&env, "MaxRateAgeUpdated"),), max_age);
+}
+
+pub fn get_max_rate_age(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&DataKey::MaxRateAge)
+        .unwrap_or(DEFAULT_MAX_RATE_AGE)
+}
+
+pub fn set_fx_spread(env: Env, source_currency: String, target_currency: String, spread_bps: u32) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if spread_bps >= 10_000 {
+        panic!("Spread must be below 100%");
+    }
+    env.storage().instance().set(
+        &DataKey::FxSpread(source_currency.clone(), target_currency.clone()),
+        &spread_bps,
+    );
+
+    env.events().publish(
+        (Symbol::new(&env, "FxSpreadUpdated"),),
+        (source_currency, target_currency, spread_bps),
+    );
+}
+
+pub fn update_fx_rate(
+    env: Env,
+    publisher: Address,
+    source_currency: String,
+    target_currency: String,
+    rate: i128,
+) {
+    publisher.require_auth();
+    if !is_rate_publisher(&env, &publisher) {
+        panic!("Unauthorized rate publisher");
+    }
+    if rate <= 0 {
+        panic!("Rate must be positive");
+    }
 
-pub fn update_fx_rate(env: Env, source_currency: String, target_currency: String, rate: i128) {
-    // In practice, restrict this to an authorized oracle or admin
     let fx_rate = ExchangeRate {
         source_currency: source_currency.clone(),
         target_currency: target_currency.clone(),
         rate,
         timestamp: env.ledger().timestamp(),
+        publisher: publisher.clone(),
     };
     env.storage().instance().set(
         &DataKey::ExchangeRate(source_currency.clone(), target_currency.clone()),
@@ -21,11 +105,30 @@ pub fn update_fx_rate(env: Env, source_currency: String, target_currency: String
             target_currency,
             fx_rate.rate,
             fx_rate.timestamp,
+            publisher,
         ),
     );
 }
 
+// Returns the mid-market rate, rejecting missing or stale rates instead of defaulting to 1:1
 pub fn get_fx_rate(env: Env, source_currency: String, target_currency: String) -> i128 {
+    lookup_fx_rate(&env, &source_currency, &target_currency)
+        .unwrap_or_else(|reason| panic!("{}", reason))
+}
+
+pub fn get_fx_quote(env: Env, source_currency: String, target_currency: String) -> FxQuote {
+    lookup_fx_quote(&env, &source_currency, &target_currency)
+        .unwrap_or_else(|reason| panic!("{}", reason))
+}
+
+pub fn lookup_fx_rate(
+    env: &Env,
+    source_currency: &String,
+    target_currency: &String,
+) -> Result<i128, &'static str> {
+    if source_currency == target_currency {
+        return Ok(RATE_SCALE);
+    }
     let fx_rate: ExchangeRate = env
         .storage()
         .instance()
@@ -33,11 +136,40 @@ pub fn get_fx_rate(env: Env, source_currency: String, target_currency: String) -
             source_currency.clone(),
             target_currency.clone(),
         ))
-        .unwrap_or(ExchangeRate {
-            source_currency,
-            target_currency,
-            rate: RATE_SCALE, // Default rate (1:1)
-            timestamp: 0,
-        });
-    fx_rate.rate
+        .ok_or("Exchange rate not available")?;
+
+    if env.ledger().timestamp() - fx_rate.timestamp > get_max_rate_age(env) {
+        return Err("Exchange rate is stale");
+    }
+    Ok(fx_rate.rate)
+}
+
+pub fn lookup_fx_quote(
+    env: &Env,
+    source_currency: &String,
+    target_currency: &String,
+) -> Result<FxQuote, &'static str> {
+    let mid = lookup_fx_rate(env, source_currency, target_currency)?;
+    let spread_bps: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::FxSpread(
+            source_currency.clone(),
+            target_currency.clone(),
+        ))
+        .unwrap_or(0);
+
+    // Half of the spread is applied on each side of the mid rate
+    let half_spread = mid * spread_bps as i128 / 20_000;
+    Ok(FxQuote {
+        mid,
+        bid: mid - half_spread,
+        ask: mid + half_spread,
+        spread_bps,
+    })
+}
+
+// Converts a source currency amount into the target currency at a RATE_SCALE fixed-point rate
+pub fn convert(amount: i128, rate: i128) -> i128 {
+    amount * rate / RATE_SCALE
 }
@@ -0,0 +1,168 @@
+use crate::settlement;
+use crate::transfer;
+use crate::types::*;
+use soroban_sdk::{Address, Env, Vec};
+
+// Appends the transfer to the sender's and recipient's persistent indexes
+pub fn record_transfer(env: &Env, transfer: &TransferRequest) {
+    append(
+        env,
+        DataKey::SenderTransferCount(transfer.sender.clone()),
+        |index| DataKey::SenderTransfer(transfer.sender.clone(), index),
+        transfer.id,
+    );
+    append(
+        env,
+        DataKey::RecipientTransferCount(transfer.recipient.clone()),
+        |index| DataKey::RecipientTransfer(transfer.recipient.clone(), index),
+        transfer.id,
+    );
+}
+
+pub fn get_transfers_by_sender(
+    env: Env,
+    sender: Address,
+    filter: TransferFilter,
+    offset: u32,
+    limit: u32,
+) -> TransferPage {
+    let count = get_count(&env, DataKey::SenderTransferCount(sender.clone()));
+    list(
+        &env,
+        count,
+        |transfer| matches_status(&filter, transfer),
+        (filter.from_timestamp, filter.to_timestamp),
+        offset,
+        limit,
+        |index| DataKey::SenderTransfer(sender.clone(), index),
+    )
+}
+
+pub fn get_transfers_by_recipient(
+    env: Env,
+    recipient: Address,
+    filter: TransferFilter,
+    offset: u32,
+    limit: u32,
+) -> TransferPage {
+    let count = get_count(&env, DataKey::RecipientTransferCount(recipient.clone()));
+    list(
+        &env,
+        count,
+        |transfer| matches_status(&filter, transfer),
+        (filter.from_timestamp, filter.to_timestamp),
+        offset,
+        limit,
+        |index| DataKey::RecipientTransfer(recipient.clone(), index),
+    )
+}
+
+// Sender's transfers that missed their settlement deadline and can be reclaimed
+pub fn get_expired_transfers(env: Env, sender: Address, offset: u32, limit: u32) -> TransferPage {
+    let count = get_count(&env, DataKey::SenderTransferCount(sender.clone()));
+    list(
+        &env,
+        count,
+        |transfer| settlement::is_expired(&env, transfer),
+        (None, None),
+        offset,
+        limit,
+        |index| DataKey::SenderTransfer(sender.clone(), index),
+    )
+}
+
+pub fn get_sender_transfer_count(env: Env, sender: Address) -> u32 {
+    get_count(&env, DataKey::SenderTransferCount(sender))
+}
+
+pub fn get_recipient_transfer_count(env: Env, recipient: Address) -> u32 {
+    get_count(&env, DataKey::RecipientTransferCount(recipient))
+}
+
+fn get_count(env: &Env, key: DataKey) -> u32 {
+    env.storage().persistent().get(&key).unwrap_or(0)
+}
+
+fn append(env: &Env, count_key: DataKey, entry_key: impl Fn(u32) -> DataKey, transfer_id: u64) {
+    let count: u32 = env.storage().persistent().get(&count_key).unwrap_or(0);
+    env.storage()
+        .persistent()
+        .set(&entry_key(count), &transfer_id);
+    env.storage().persistent().set(&count_key, &(count + 1));
+}
+
+// Reads at most `limit` index positions from `offset`, so a page costs the same however long the
+// history is. Indexes are in initiation order, so the time range is found by binary search and the
+// scan stops at the first transfer past its end.
+fn list(
+    env: &Env,
+    count: u32,
+    filter: impl Fn(&TransferRequest) -> bool,
+    (from_timestamp, to_timestamp): (Option<u64>, Option<u64>),
+    offset: u32,
+    limit: u32,
+    entry_key: impl Fn(u32) -> DataKey,
+) -> TransferPage {
+    let start = match from_timestamp {
+        Some(from) => offset.max(first_at_or_after(env, count, from, &entry_key)),
+        None => offset,
+    };
+    let end = start.saturating_add(limit).min(count);
+
+    let mut transfers = Vec::new(env);
+    let mut position = start;
+    let mut past_range = false;
+    while position < end {
+        let transfer = load_entry(env, &entry_key, position);
+        if to_timestamp.is_some_and(|to| transfer.timestamp > to) {
+            past_range = true;
+            break;
+        }
+        position += 1;
+        if filter(&transfer) {
+            transfers.push_back(transfer);
+        }
+    }
+
+    let next_offset = if past_range || position >= count {
+        None
+    } else {
+        Some(position)
+    };
+    TransferPage {
+        transfers,
+        next_offset,
+    }
+}
+
+// First index position whose transfer was initiated at or after `timestamp`
+fn first_at_or_after(
+    env: &Env,
+    count: u32,
+    timestamp: u64,
+    entry_key: &impl Fn(u32) -> DataKey,
+) -> u32 {
+    let (mut low, mut high) = (0u32, count);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if load_entry(env, entry_key, mid).timestamp < timestamp {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+fn load_entry(env: &Env, entry_key: &impl Fn(u32) -> DataKey, index: u32) -> TransferRequest {
+    let transfer_id: u64 = env
+        .storage()
+        .persistent()
+        .get(&entry_key(index))
+        .unwrap_or_else(|| panic!("Transfer not found"));
+    transfer::load_transfer(env, transfer_id)
+}
+
+fn matches_status(filter: &TransferFilter, transfer: &TransferRequest) -> bool {
+    filter.statuses.is_empty() || filter.statuses.contains(&transfer.status)
+}
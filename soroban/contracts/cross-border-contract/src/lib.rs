@@ -1,9 +1,12 @@
 #![no_std]
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String, Symbol};
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, String, Symbol, Vec};
 
 mod compliance;
 mod fees;
 mod fx;
+mod history;
+mod liquidity;
+mod screening;
 mod settlement;
 mod transfer;
 mod types;
@@ -14,6 +17,9 @@ mod test;
 pub use compliance::*;
 pub use fees::*;
 pub use fx::*;
+pub use history::*;
+pub use liquidity::*;
+pub use screening::*;
 pub use settlement::*;
 pub use transfer::*;
 pub use types::*;
@@ -32,6 +38,11 @@ impl CrossBorderPayment {
         urgency_multiplier: u32,
     ) {
         admin.require_auth();
+        if env.storage().instance().has(&DataKey::Admin) {
+            panic!("Contract already initialized");
+        }
+        env.storage().instance().set(&DataKey::Admin, &admin);
+
         let fee_structure = FeeStructure {
             base_fee,
             percentage,
@@ -49,27 +60,80 @@ impl CrossBorderPayment {
     }
 
     // Transfer functions
-    pub fn initiate_transfer(
+    pub fn initiate_transfer(env: Env, sender: Address, params: TransferParams) -> u64 {
+        transfer::initiate_transfer(env, sender, params)
+    }
+
+    // Compliance functions
+    pub fn add_compliance_officer(env: Env, officer: Address, organization: Address) {
+        compliance::add_compliance_officer(env, officer, organization)
+    }
+
+    pub fn remove_compliance_officer(env: Env, officer: Address) {
+        compliance::remove_compliance_officer(env, officer)
+    }
+
+    pub fn set_tier_limits(env: Env, tier: RiskTier, limits: TierLimits) {
+        compliance::set_tier_limits(env, tier, limits)
+    }
+
+    pub fn approve_user(
         env: Env,
-        sender: Address,
-        recipient: Address,
-        amount: i128,
-        currency: String,
-        destination_network: String,
-    ) -> u64 {
-        transfer::initiate_transfer(
+        officer: Address,
+        user: Address,
+        documents: BytesN<32>,
+        identity_hash: BytesN<32>,
+        risk_tier: RiskTier,
+        expires_at: u64,
+    ) {
+        compliance::approve_user(
             env,
-            sender,
-            recipient,
-            amount,
-            currency,
-            destination_network,
+            officer,
+            user,
+            documents,
+            identity_hash,
+            risk_tier,
+            expires_at,
         )
     }
 
-    // Compliance functions
-    pub fn verify_compliance(env: Env, user: Address, documents: BytesN<32>) {
-        compliance::verify_compliance(env, user, documents)
+    pub fn reject_user(
+        env: Env,
+        officer: Address,
+        user: Address,
+        documents: BytesN<32>,
+        identity_hash: BytesN<32>,
+    ) {
+        compliance::reject_user(env, officer, user, documents, identity_hash)
+    }
+
+    pub fn get_compliance(env: Env, user: Address) -> ComplianceData {
+        compliance::get_compliance(env, user)
+    }
+
+    // Screening functions
+    pub fn set_compliance_admin(env: Env, compliance_admin: Address) {
+        screening::set_compliance_admin(env, compliance_admin)
+    }
+
+    pub fn update_denylist(env: Env, update: DenylistUpdate) -> u32 {
+        screening::update_denylist(env, update)
+    }
+
+    pub fn get_denylist_info(env: Env) -> DenylistInfo {
+        screening::get_denylist_info(env)
+    }
+
+    pub fn verify_denylist_proof(env: Env, identifier: BytesN<32>, proof: Vec<BytesN<32>>) -> bool {
+        screening::verify_denylist_proof(env, identifier, proof)
+    }
+
+    pub fn report_denylisted_identifier(env: Env, identifier: BytesN<32>, proof: Vec<BytesN<32>>) {
+        screening::report_denylisted_identifier(env, identifier, proof)
+    }
+
+    pub fn is_denylisted(env: Env, party: Address) -> bool {
+        screening::is_denylisted(&env, &party)
     }
 
     // Settlement functions
@@ -77,24 +141,171 @@ impl CrossBorderPayment {
         settlement::execute_settlement(env, transfer_id, org)
     }
 
+    pub fn execute_batch_settlement(
+        env: Env,
+        org: Address,
+        transfer_ids: Vec<u64>,
+    ) -> BatchSettlement {
+        settlement::execute_batch_settlement(env, org, transfer_ids)
+    }
+
+    pub fn get_batch_settlement(env: Env, batch_id: u64) -> BatchSettlement {
+        settlement::get_batch_settlement(env, batch_id)
+    }
+
     pub fn refund_transfer(env: Env, transfer_id: u64, org: Address) {
         settlement::refund_transfer(env, transfer_id, org)
     }
 
+    pub fn release_rejected_transfer(env: Env, transfer_id: u64) {
+        settlement::release_rejected_transfer(env, transfer_id)
+    }
+
+    pub fn reclaim_expired_transfer(env: Env, transfer_id: u64) {
+        settlement::reclaim_expired_transfer(env, transfer_id)
+    }
+
+    pub fn set_default_settlement_window(env: Env, window: u64) {
+        settlement::set_default_settlement_window(env, window)
+    }
+
+    pub fn get_default_settlement_window(env: Env) -> u64 {
+        settlement::get_default_settlement_window(&env)
+    }
+
+    // Liquidity functions
+    pub fn set_currency_token(env: Env, currency: String, token: Address) {
+        liquidity::set_currency_token(env, currency, token)
+    }
+
+    pub fn deposit_liquidity(env: Env, currency: String, amount: i128) {
+        liquidity::deposit_liquidity(env, currency, amount)
+    }
+
+    pub fn withdraw_liquidity(env: Env, currency: String, amount: i128) {
+        liquidity::withdraw_liquidity(env, currency, amount)
+    }
+
+    pub fn get_liquidity(env: Env, currency: String) -> i128 {
+        liquidity::get_liquidity(env, currency)
+    }
+
     // Fee functions
     pub fn calculate_fees(env: Env, amount: i128, is_urgent: bool) -> i128 {
         fees::calculate_fees(env, amount, is_urgent)
     }
 
+    pub fn quote_fee(env: Env, sender: Address, params: TransferParams) -> i128 {
+        fees::quote_fee(env, sender, params)
+    }
+
+    pub fn set_fee_schedule(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        destination_network: String,
+        schedule: FeeSchedule,
+    ) {
+        fees::set_fee_schedule(
+            env,
+            source_currency,
+            target_currency,
+            destination_network,
+            schedule,
+        )
+    }
+
+    pub fn remove_fee_schedule(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        destination_network: String,
+    ) {
+        fees::remove_fee_schedule(env, source_currency, target_currency, destination_network)
+    }
+
+    pub fn get_fee_schedule(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        destination_network: String,
+    ) -> FeeSchedule {
+        fees::get_fee_schedule(env, source_currency, target_currency, destination_network)
+    }
+
+    pub fn set_fee_promotion(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        destination_network: String,
+        promotion: FeePromotion,
+    ) {
+        fees::set_fee_promotion(
+            env,
+            source_currency,
+            target_currency,
+            destination_network,
+            promotion,
+        )
+    }
+
+    pub fn remove_fee_promotion(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        destination_network: String,
+    ) {
+        fees::remove_fee_promotion(env, source_currency, target_currency, destination_network)
+    }
+
+    pub fn set_treasury(env: Env, treasury: Address) {
+        fees::set_treasury(env, treasury)
+    }
+
+    pub fn get_treasury(env: Env) -> Result<Address, ContractError> {
+        fees::get_treasury(&env)
+    }
+
     // FX functions
-    pub fn update_fx_rate(env: Env, source_currency: String, target_currency: String, rate: i128) {
-        fx::update_fx_rate(env, source_currency, target_currency, rate)
+    pub fn add_rate_publisher(env: Env, publisher: Address) {
+        fx::add_rate_publisher(env, publisher)
+    }
+
+    pub fn remove_rate_publisher(env: Env, publisher: Address) {
+        fx::remove_rate_publisher(env, publisher)
+    }
+
+    pub fn set_max_rate_age(env: Env, max_age: u64) {
+        fx::set_max_rate_age(env, max_age)
+    }
+
+    pub fn set_fx_spread(
+        env: Env,
+        source_currency: String,
+        target_currency: String,
+        spread_bps: u32,
+    ) {
+        fx::set_fx_spread(env, source_currency, target_currency, spread_bps)
+    }
+
+    pub fn update_fx_rate(
+        env: Env,
+        publisher: Address,
+        source_currency: String,
+        target_currency: String,
+        rate: i128,
+    ) {
+        fx::update_fx_rate(env, publisher, source_currency, target_currency, rate)
     }
 
     pub fn get_fx_rate(env: Env, source_currency: String, target_currency: String) -> i128 {
         fx::get_fx_rate(env, source_currency, target_currency)
     }
 
+    pub fn get_fx_quote(env: Env, source_currency: String, target_currency: String) -> FxQuote {
+        fx::get_fx_quote(env, source_currency, target_currency)
+    }
+
     // Query functions
     pub fn get_transfer_status(env: Env, transfer_id: u64) -> SettlementStatus {
         settlement::get_transfer_status(env, transfer_id)
@@ -103,4 +314,45 @@ impl CrossBorderPayment {
     pub fn get_transfer_details(env: Env, transfer_id: u64) -> TransferRequest {
         transfer::get_transfer_details(env, transfer_id)
     }
+
+    pub fn get_transfers_by_sender(
+        env: Env,
+        sender: Address,
+        filter: TransferFilter,
+        offset: u32,
+        limit: u32,
+    ) -> TransferPage {
+        history::get_transfers_by_sender(env, sender, filter, offset, limit)
+    }
+
+    pub fn get_transfers_by_recipient(
+        env: Env,
+        recipient: Address,
+        filter: TransferFilter,
+        offset: u32,
+        limit: u32,
+    ) -> TransferPage {
+        history::get_transfers_by_recipient(env, recipient, filter, offset, limit)
+    }
+
+    pub fn is_transfer_expired(env: Env, transfer_id: u64) -> bool {
+        settlement::is_transfer_expired(env, transfer_id)
+    }
+
+    pub fn get_expired_transfers(
+        env: Env,
+        sender: Address,
+        offset: u32,
+        limit: u32,
+    ) -> TransferPage {
+        history::get_expired_transfers(env, sender, offset, limit)
+    }
+
+    pub fn get_sender_transfer_count(env: Env, sender: Address) -> u32 {
+        history::get_sender_transfer_count(env, sender)
+    }
+
+    pub fn get_recipient_transfer_count(env: Env, recipient: Address) -> u32 {
+        history::get_recipient_transfer_count(env, recipient)
+    }
 }
@@ -0,0 +1,88 @@
+use crate::types::*;
+use soroban_sdk::{token, Address, Env, String, Symbol};
+
+pub fn set_currency_token(env: Env, currency: String, token: Address) {
+    let admin = get_admin(&env);
+    admin.require_auth();
+
+    env.storage()
+        .instance()
+        .set(&DataKey::CurrencyToken(currency.clone()), &token);
+
+    env.events()
+        .publish((Symbol::new(&env, "CurrencyTokenSet"),), (currency, token));
+}
+
+pub fn deposit_liquidity(env: Env, currency: String, amount: i128) {
+    let admin = get_admin(&env);
+    admin.require_auth();
+
+    if amount <= 0 {
+        panic!("Amount must be positive");
+    }
+
+    let token = get_currency_token(&env, &currency);
+    token::Client::new(&env, &token).transfer(&admin, &env.current_contract_address(), &amount);
+    credit_reserve(&env, &currency, amount);
+
+    env.events().publish(
+        (Symbol::new(&env, "LiquidityDeposited"),),
+        (currency, amount),
+    );
+}
+
+pub fn withdraw_liquidity(env: Env, currency: String, amount: i128) {
+    let admin = get_admin(&env);
+    admin.require_auth();
+
+    if amount <= 0 {
+        panic!("Amount must be positive");
+    }
+
+    debit_reserve(&env, &currency, amount);
+    let token = get_currency_token(&env, &currency);
+    token::Client::new(&env, &token).transfer(&env.current_contract_address(), &admin, &amount);
+
+    env.events().publish(
+        (Symbol::new(&env, "LiquidityWithdrawn"),),
+        (currency, amount),
+    );
+}
+
+pub fn get_liquidity(env: Env, currency: String) -> i128 {
+    env.storage()
+        .instance()
+        .get(&DataKey::Liquidity(currency))
+        .unwrap_or(0)
+}
+
+pub fn get_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .unwrap_or_else(|| panic!("Contract not initialized"))
+}
+
+pub fn get_currency_token(env: &Env, currency: &String) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::CurrencyToken(currency.clone()))
+        .unwrap_or_else(|| panic!("Currency not supported"))
+}
+
+pub fn credit_reserve(env: &Env, currency: &String, amount: i128) {
+    let reserve = get_liquidity(env.clone(), currency.clone());
+    env.storage()
+        .instance()
+        .set(&DataKey::Liquidity(currency.clone()), &(reserve + amount));
+}
+
+pub fn debit_reserve(env: &Env, currency: &String, amount: i128) {
+    let reserve = get_liquidity(env.clone(), currency.clone());
+    if reserve < amount {
+        panic!("Insufficient liquidity");
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::Liquidity(currency.clone()), &(reserve - amount));
+}
@@ -0,0 +1,143 @@
+use crate::liquidity;
+use crate::types::*;
+use soroban_sdk::{Address, Bytes, BytesN, Env, Symbol, Vec};
+
+pub fn set_compliance_admin(env: Env, compliance_admin: Address) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    env.storage()
+        .instance()
+        .set(&DataKey::ComplianceAdmin, &compliance_admin);
+
+    env.events()
+        .publish((Symbol::new(&env, "ComplianceAdminSet"),), compliance_admin);
+}
+
+// Falls back to the contract admin until a dedicated compliance admin is appointed
+pub fn get_compliance_admin(env: &Env) -> Address {
+    env.storage()
+        .instance()
+        .get(&DataKey::ComplianceAdmin)
+        .unwrap_or_else(|| liquidity::get_admin(env))
+}
+
+pub fn update_denylist(env: Env, update: DenylistUpdate) -> u32 {
+    let compliance_admin = get_compliance_admin(&env);
+    compliance_admin.require_auth();
+
+    let storage = env.storage().persistent();
+    for address in update.add_addresses.iter() {
+        storage.set(&DataKey::DeniedAddress(address), &true);
+    }
+    for address in update.remove_addresses.iter() {
+        storage.remove(&DataKey::DeniedAddress(address));
+    }
+    for identifier in update.add_identifiers.iter() {
+        storage.set(&DataKey::DeniedIdentifier(identifier), &true);
+    }
+    for identifier in update.remove_identifiers.iter() {
+        storage.remove(&DataKey::DeniedIdentifier(identifier));
+    }
+
+    let info = DenylistInfo {
+        version: get_denylist_info(env.clone()).version + 1,
+        merkle_root: update.merkle_root,
+        updated_at: env.ledger().timestamp(),
+    };
+    env.storage().instance().set(&DataKey::DenylistInfo, &info);
+
+    env.events().publish(
+        (Symbol::new(&env, "DenylistUpdated"),),
+        (
+            info.version,
+            info.merkle_root,
+            update.add_addresses.len() + update.add_identifiers.len(),
+            update.remove_addresses.len() + update.remove_identifiers.len(),
+        ),
+    );
+
+    info.version
+}
+
+pub fn get_denylist_info(env: Env) -> DenylistInfo {
+    env.storage()
+        .instance()
+        .get(&DataKey::DenylistInfo)
+        .unwrap_or(DenylistInfo {
+            version: 0,
+            merkle_root: BytesN::from_array(&env, &[0; 32]),
+            updated_at: 0,
+        })
+}
+
+// Domain tags keeping leaf and internal node hashes apart, so a node cannot pass as an identifier
+const MERKLE_LEAF_TAG: u8 = 0x00;
+const MERKLE_NODE_TAG: u8 = 0x01;
+
+// Checks a sorted-pair SHA-256 Merkle proof for an identifier against the current list root.
+// Leaves hash as sha256(0x00 || identifier) and internal nodes as sha256(0x01 || lower || higher).
+pub fn verify_denylist_proof(env: Env, identifier: BytesN<32>, proof: Vec<BytesN<32>>) -> bool {
+    let mut leaf = Bytes::from_array(&env, &[MERKLE_LEAF_TAG]);
+    leaf.append(&identifier.into());
+    let mut node: BytesN<32> = env.crypto().sha256(&leaf).to_bytes();
+    for sibling in proof.iter() {
+        let mut preimage = Bytes::from_array(&env, &[MERKLE_NODE_TAG]);
+        if node < sibling {
+            preimage.append(&node.into());
+            preimage.append(&sibling.into());
+        } else {
+            preimage.append(&sibling.into());
+            preimage.append(&node.into());
+        }
+        node = env.crypto().sha256(&preimage).to_bytes();
+    }
+    node == get_denylist_info(env).merkle_root
+}
+
+// Lets anyone promote an identifier from the off-chain list to the on-chain denylist with a proof
+pub fn report_denylisted_identifier(env: Env, identifier: BytesN<32>, proof: Vec<BytesN<32>>) {
+    if !verify_denylist_proof(env.clone(), identifier.clone(), proof) {
+        panic!("Invalid denylist proof");
+    }
+    env.storage()
+        .persistent()
+        .set(&DataKey::DeniedIdentifier(identifier.clone()), &true);
+
+    env.events().publish(
+        (Symbol::new(&env, "DenylistIdentifierReported"),),
+        (identifier, get_denylist_info(env.clone()).version),
+    );
+}
+
+pub fn is_denylisted(env: &Env, party: &Address) -> bool {
+    let storage = env.storage().persistent();
+    if storage.has(&DataKey::DeniedAddress(party.clone())) {
+        return true;
+    }
+    let compliance: Option<ComplianceData> = storage.get(&DataKey::Compliance(party.clone()));
+    match compliance {
+        Some(data) => storage.has(&DataKey::DeniedIdentifier(data.identity_hash)),
+        None => false,
+    }
+}
+
+// Checks both parties of a transfer without publishing anything, for callers that reject on a hit
+pub fn any_party_denylisted(env: &Env, sender: &Address, recipient: &Address) -> bool {
+    is_denylisted(env, sender) || is_denylisted(env, recipient)
+}
+
+// Screens both parties of a transfer and publishes the outcome for each, returning true on any hit
+pub fn screen_parties(env: &Env, transfer_id: u64, sender: &Address, recipient: &Address) -> bool {
+    let version = get_denylist_info(env.clone()).version;
+    let mut hit = false;
+    for party in [sender, recipient] {
+        let party_hit = is_denylisted(env, party);
+        env.events().publish(
+            (Symbol::new(env, "PartyScreened"),),
+            (transfer_id, party.clone(), party_hit, version),
+        );
+        hit = hit || party_hit;
+    }
+    hit
+}
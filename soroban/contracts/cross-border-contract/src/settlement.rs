@@ -1,86 +1,403 @@
 use crate::compliance;
+use crate::fees;
+use crate::fx;
+use crate::liquidity;
+use crate::screening;
+use crate::transfer;
 use crate::types::*;
-use soroban_sdk::Symbol;
-use soroban_sdk::{Address, Env};
+use soroban_sdk::{token, Address, Env, Map, String, Symbol, Vec};
 
 pub fn execute_settlement(env: Env, transfer_id: u64, org: Address) {
     org.require_auth();
 
     // Get transfer details
-    let transfer: TransferRequest = env
-        .storage()
-        .instance()
-        .get(&DataKey::Transfer(transfer_id))
-        .unwrap_or_else(|| panic!("Transfer not found"));
+    let mut transfer = transfer::load_transfer(&env, transfer_id);
+
+    // Verify current status
+    if transfer.status != SettlementStatus::Pending {
+        panic!("Transfer is not in pending state");
+    }
+
+    // Sanctions hits block the transfer and freeze the escrow instead of settling it
+    if block_if_denylisted(&env, &mut transfer, &org) {
+        return;
+    }
+
+    if let Err(reason) = settle(&env, &mut transfer, &org) {
+        panic!("{}", reason);
+    }
+}
+
+// Settles many transfers at once and reports the resulting obligations netted per organization pair.
+// Each transfer still pays out gross; the net figures are informational for off-chain reconciliation.
+pub fn execute_batch_settlement(env: Env, org: Address, transfer_ids: Vec<u64>) -> BatchSettlement {
+    org.require_auth();
+
+    if transfer_ids.is_empty() {
+        panic!("Batch is empty");
+    }
+
+    let batch_id = next_batch_id(&env);
+    let mut settled = Vec::new(&env);
+    let mut rejected = Vec::new(&env);
+    let mut pairs: Map<(Address, Address), NetSettlement> = Map::new(&env);
+
+    for transfer_id in transfer_ids.iter() {
+        // Each transfer is validated on its own; failures are reported without aborting the batch
+        let result = match env
+            .storage()
+            .persistent()
+            .get::<DataKey, TransferRequest>(&DataKey::Transfer(transfer_id))
+        {
+            None => Err("Transfer not found"),
+            Some(transfer) if transfer.status != SettlementStatus::Pending => {
+                Err("Transfer is not in pending state")
+            }
+            Some(mut transfer) => {
+                if block_if_denylisted(&env, &mut transfer, &org) {
+                    Err("Party is denylisted")
+                } else {
+                    settle(&env, &mut transfer, &org).map(|_| transfer)
+                }
+            }
+        };
+
+        match result {
+            Ok(transfer) => {
+                settled.push_back(transfer_id);
+                add_to_net(&env, &mut pairs, &transfer);
+            }
+            Err(reason) => {
+                rejected.push_back(transfer_id);
+                env.events().publish(
+                    (Symbol::new(&env, "BatchTransferRejected"),),
+                    (batch_id, transfer_id, String::from_str(&env, reason)),
+                );
+            }
+        }
+    }
+
+    for net in pairs.values().iter() {
+        env.events().publish(
+            (Symbol::new(&env, "NetSettlement"),),
+            (
+                batch_id,
+                net.org_a.clone(),
+                net.org_b.clone(),
+                net.flows.clone(),
+                net.transfer_ids.len(),
+            ),
+        );
+    }
+
+    let batch = BatchSettlement {
+        id: batch_id,
+        org,
+        settled,
+        rejected,
+        net_settlements: pairs.values(),
+        timestamp: env.ledger().timestamp(),
+    };
+    env.storage()
+        .persistent()
+        .set(&DataKey::BatchSettlement(batch_id), &batch);
+
+    env.events().publish(
+        (Symbol::new(&env, "BatchSettled"),),
+        (
+            batch_id,
+            batch.org.clone(),
+            batch.settled.len(),
+            batch.rejected.len(),
+        ),
+    );
+
+    batch
+}
+
+pub fn get_batch_settlement(env: Env, batch_id: u64) -> BatchSettlement {
+    env.storage()
+        .persistent()
+        .get(&DataKey::BatchSettlement(batch_id))
+        .unwrap_or_else(|| panic!("Batch settlement not found"))
+}
+
+fn block_if_denylisted(env: &Env, transfer: &mut TransferRequest, org: &Address) -> bool {
+    if !screening::screen_parties(env, transfer.id, &transfer.sender, &transfer.recipient) {
+        return false;
+    }
+    transfer.status = SettlementStatus::Rejected;
+    transfer::save_transfer(env, transfer);
+    env.events().publish(
+        (Symbol::new(env, "TransferBlocked"),),
+        (transfer.id, org.clone()),
+    );
+    true
+}
+
+// Validates a pending transfer and, only once every check passes, pays it out
+fn settle(env: &Env, transfer: &mut TransferRequest, org: &Address) -> Result<(), &'static str> {
+    if env.ledger().timestamp() >= transfer.deadline {
+        return Err("Transfer has expired");
+    }
 
     // Verify compliance for sender and recipient
-    if !compliance::is_compliant(&env, &transfer.sender)
-        || !compliance::is_compliant(&env, &transfer.recipient)
+    if !compliance::is_compliant(env, &transfer.sender)
+        || !compliance::is_compliant(env, &transfer.recipient)
     {
-        panic!("Compliance check failed");
+        return Err("Compliance check failed");
     }
 
-    // Verify current status
-    let status: SettlementStatus = env
-        .storage()
-        .instance()
-        .get(&DataKey::Settlement(transfer_id))
-        .unwrap_or(SettlementStatus::Pending);
-    if status != SettlementStatus::Pending {
-        panic!("Transfer is not in pending state");
+    // Check the sender's risk tier limits
+    compliance::check_transfer_limits(env, &transfer.sender, transfer.amount)?;
+
+    // Escrowed funds move into the source reserve, the payout comes out of the target reserve
+    let quote = fx::lookup_fx_quote(env, &transfer.currency, &transfer.target_currency)?;
+    let payout_amount = fx::convert(transfer.amount, quote.bid);
+    if payout_amount <= 0 {
+        return Err("Payout amount must be positive");
+    }
+    if liquidity::get_liquidity(env.clone(), transfer.target_currency.clone()) < payout_amount {
+        return Err("Insufficient liquidity");
     }
 
-    // Update status to Approved
-    env.storage().instance().set(
-        &DataKey::Settlement(transfer_id),
-        &SettlementStatus::Approved,
-    );
+    // The fee destination is resolved before any state changes so a batch can skip this transfer
+    let treasury = if transfer.fee > 0 {
+        Some(fees::get_treasury(env).map_err(|_| "Treasury not configured")?)
+    } else {
+        None
+    };
+
+    compliance::record_volume(env, &transfer.sender, transfer.amount);
+    liquidity::credit_reserve(env, &transfer.currency, transfer.amount);
+    liquidity::debit_reserve(env, &transfer.target_currency, payout_amount);
+
+    // The escrowed fee is collected into the treasury
+    if let Some(treasury) = treasury {
+        let source_token = liquidity::get_currency_token(env, &transfer.currency);
+        token::Client::new(env, &source_token).transfer(
+            &env.current_contract_address(),
+            &treasury,
+            &transfer.fee,
+        );
+        env.events().publish(
+            (Symbol::new(env, "FeeCollected"),),
+            (
+                transfer.id,
+                treasury,
+                transfer.currency.clone(),
+                transfer.fee,
+            ),
+        );
+    }
 
-    // Simulate settlement with destination network (mocked for simplicity)
-    // In practice, integrate with external network APIs
-    env.storage().instance().set(
-        &DataKey::Settlement(transfer_id),
-        &SettlementStatus::Settled,
+    let target_token = liquidity::get_currency_token(env, &transfer.target_currency);
+    token::Client::new(env, &target_token).transfer(
+        &env.current_contract_address(),
+        &transfer.recipient,
+        &payout_amount,
     );
 
+    transfer.payout_amount = payout_amount;
+    transfer.status = SettlementStatus::Settled;
+    transfer::save_transfer(env, transfer);
+
     env.events().publish(
-        (Symbol::new(&env, "TransferSettled"),),
+        (Symbol::new(env, "TransferSettled"),),
         (
-            transfer_id,
-            org,
+            transfer.id,
+            org.clone(),
             transfer.amount,
-            transfer.currency,
-            transfer.destination_network,
+            transfer.currency.clone(),
+            payout_amount,
+            transfer.target_currency.clone(),
+            transfer.destination_network.clone(),
         ),
     );
+    Ok(())
+}
+
+// Adds a settled transfer as an obligation from the sender's organization to the recipient's.
+// No funds move here; the transfer has already settled gross.
+fn add_to_net(
+    env: &Env,
+    pairs: &mut Map<(Address, Address), NetSettlement>,
+    transfer: &TransferRequest,
+) {
+    let from = compliance::get_compliance(env.clone(), transfer.sender.clone()).organization;
+    let to = compliance::get_compliance(env.clone(), transfer.recipient.clone()).organization;
+    if from == to {
+        return;
+    }
+
+    // Pairs are keyed in address order; positive amounts are owed by org_a to org_b
+    let (org_a, org_b, amount) = if from < to {
+        (from, to, transfer.amount)
+    } else {
+        (to, from, -transfer.amount)
+    };
+    let key = (org_a.clone(), org_b.clone());
+    let mut net = pairs.get(key.clone()).unwrap_or(NetSettlement {
+        org_a,
+        org_b,
+        flows: Vec::new(env),
+        transfer_ids: Vec::new(env),
+    });
+
+    let mut flows = Vec::new(env);
+    let mut found = false;
+    for mut flow in net.flows.iter() {
+        if flow.currency == transfer.currency {
+            flow.amount += amount;
+            found = true;
+        }
+        flows.push_back(flow);
+    }
+    if !found {
+        flows.push_back(NetFlow {
+            currency: transfer.currency.clone(),
+            amount,
+        });
+    }
+    net.flows = flows;
+    net.transfer_ids.push_back(transfer.id);
+    pairs.set(key, net);
+}
+
+fn next_batch_id(env: &Env) -> u64 {
+    let batch_id = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextBatchId)
+        .unwrap_or(1u64);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextBatchId, &(batch_id + 1));
+    batch_id
 }
 
 pub fn refund_transfer(env: Env, transfer_id: u64, org: Address) {
     org.require_auth();
 
     // Verify current status
-    let status: SettlementStatus = env
-        .storage()
-        .instance()
-        .get(&DataKey::Settlement(transfer_id))
-        .unwrap_or(SettlementStatus::Pending);
-    if status != SettlementStatus::Pending && status != SettlementStatus::Approved {
+    let mut transfer = transfer::load_transfer(&env, transfer_id);
+    if transfer.status != SettlementStatus::Pending && transfer.status != SettlementStatus::Approved
+    {
         panic!("Transfer cannot be refunded");
     }
 
-    // Update status to Refunded
-    env.storage().instance().set(
-        &DataKey::Settlement(transfer_id),
-        &SettlementStatus::Refunded,
+    // Return the escrowed principal and fees to the sender
+    let source_token = liquidity::get_currency_token(&env, &transfer.currency);
+    token::Client::new(&env, &source_token).transfer(
+        &env.current_contract_address(),
+        &transfer.sender,
+        &(transfer.amount + transfer.fee),
     );
 
+    // Update status to Refunded
+    transfer.status = SettlementStatus::Refunded;
+    transfer::save_transfer(&env, &transfer);
+
     env.events()
         .publish((Symbol::new(&env, "TransferRefunded"),), (transfer_id, org));
 }
 
-pub fn get_transfer_status(env: Env, transfer_id: u64) -> SettlementStatus {
+// Lets the compliance admin return the frozen escrow of a transfer blocked at settlement,
+// e.g. after a false positive is delisted. Escrow of a still-denylisted sender stays frozen.
+pub fn release_rejected_transfer(env: Env, transfer_id: u64) {
+    let compliance_admin = screening::get_compliance_admin(&env);
+    compliance_admin.require_auth();
+
+    let mut transfer = transfer::load_transfer(&env, transfer_id);
+    if transfer.status != SettlementStatus::Rejected {
+        panic!("Transfer is not rejected");
+    }
+    if screening::is_denylisted(&env, &transfer.sender) {
+        panic!("Sender is denylisted");
+    }
+
+    let source_token = liquidity::get_currency_token(&env, &transfer.currency);
+    token::Client::new(&env, &source_token).transfer(
+        &env.current_contract_address(),
+        &transfer.sender,
+        &(transfer.amount + transfer.fee),
+    );
+
+    transfer.status = SettlementStatus::Refunded;
+    transfer::save_transfer(&env, &transfer);
+
+    env.events().publish(
+        (Symbol::new(&env, "RejectedTransferReleased"),),
+        (
+            transfer_id,
+            compliance_admin,
+            transfer.amount + transfer.fee,
+        ),
+    );
+}
+
+// Lets the sender take back the escrow of a transfer nobody settled before its deadline
+pub fn reclaim_expired_transfer(env: Env, transfer_id: u64) {
+    let mut transfer = transfer::load_transfer(&env, transfer_id);
+    transfer.sender.require_auth();
+
+    if transfer.status != SettlementStatus::Pending && transfer.status != SettlementStatus::Approved
+    {
+        panic!("Transfer cannot be reclaimed");
+    }
+    if env.ledger().timestamp() < transfer.deadline {
+        panic!("Settlement deadline has not passed");
+    }
+
+    let source_token = liquidity::get_currency_token(&env, &transfer.currency);
+    token::Client::new(&env, &source_token).transfer(
+        &env.current_contract_address(),
+        &transfer.sender,
+        &(transfer.amount + transfer.fee),
+    );
+
+    transfer.status = SettlementStatus::Expired;
+    transfer::save_transfer(&env, &transfer);
+
+    env.events().publish(
+        (Symbol::new(&env, "TransferReclaimed"),),
+        (transfer_id, transfer.sender, transfer.amount + transfer.fee),
+    );
+}
+
+pub fn is_transfer_expired(env: Env, transfer_id: u64) -> bool {
+    let transfer = transfer::load_transfer(&env, transfer_id);
+    is_expired(&env, &transfer)
+}
+
+// Unsettled transfers past their deadline, whose escrow the sender can reclaim
+pub fn is_expired(env: &Env, transfer: &TransferRequest) -> bool {
+    (transfer.status == SettlementStatus::Pending || transfer.status == SettlementStatus::Approved)
+        && env.ledger().timestamp() >= transfer.deadline
+}
+
+pub fn set_default_settlement_window(env: Env, window: u64) {
+    let admin = liquidity::get_admin(&env);
+    admin.require_auth();
+
+    if window == 0 || window > MAX_SETTLEMENT_WINDOW {
+        panic!("Invalid settlement window");
+    }
+    env.storage()
+        .instance()
+        .set(&DataKey::DefaultSettlementWindow, &window);
+
+    env.events()
+        .publish((Symbol::new(&env, "SettlementWindowUpdated"),), window);
+}
+
+pub fn get_default_settlement_window(env: &Env) -> u64 {
     env.storage()
         .instance()
-        .get(&DataKey::Settlement(transfer_id))
-        .unwrap_or(SettlementStatus::Pending)
+        .get(&DataKey::DefaultSettlementWindow)
+        .unwrap_or(DEFAULT_SETTLEMENT_WINDOW)
+}
+
+pub fn get_transfer_status(env: Env, transfer_id: u64) -> SettlementStatus {
+    transfer::load_transfer(&env, transfer_id).status
 }
@@ -1,53 +1,68 @@
+use crate::fees;
+use crate::history;
+use crate::liquidity;
+use crate::screening;
+use crate::settlement;
 use crate::types::*;
-use soroban_sdk::{Address, Env, String, Symbol, Vec};
+use soroban_sdk::{token, Address, Env, Symbol};
 
-pub fn initiate_transfer(
-    env: Env,
-    sender: Address,
-    recipient: Address,
-    amount: i128,
-    currency: String,
-    destination_network: String,
-) -> u64 {
+pub fn initiate_transfer(env: Env, sender: Address, params: TransferParams) -> u64 {
     sender.require_auth();
 
     // Validate inputs
-    if amount <= 0 {
+    if params.amount <= 0 {
         panic!("Amount must be positive");
     }
+    if params.settlement_window > MAX_SETTLEMENT_WINDOW {
+        panic!("Settlement window too long");
+    }
+    let source_token = liquidity::get_currency_token(&env, &params.currency);
+    liquidity::get_currency_token(&env, &params.target_currency);
+
+    // A rejected initiation is rolled back along with anything it publishes, so hits are
+    // rejected before screening results are recorded
+    if screening::any_party_denylisted(&env, &sender, &params.recipient) {
+        panic!("Party is denylisted");
+    }
+
+    // Lock the principal plus fees in escrow until settlement or refund
+    let fee = fees::quote_fee(env.clone(), sender.clone(), params.clone());
+    token::Client::new(&env, &source_token).transfer(
+        &sender,
+        &env.current_contract_address(),
+        &(params.amount + fee),
+    );
 
     // Get next transfer ID
     let transfer_id = next_transfer_id(&env);
 
+    screening::screen_parties(&env, transfer_id, &sender, &params.recipient);
+
+    let window = if params.settlement_window == 0 {
+        settlement::get_default_settlement_window(&env)
+    } else {
+        params.settlement_window
+    };
+
     // Create transfer request
     let transfer = TransferRequest {
+        id: transfer_id,
+        status: SettlementStatus::Pending,
         sender: sender.clone(),
-        recipient,
-        amount,
-        currency,
-        destination_network,
+        recipient: params.recipient,
+        amount: params.amount,
+        currency: params.currency,
+        target_currency: params.target_currency,
+        destination_network: params.destination_network,
+        fee,
+        payout_amount: 0,
         timestamp: env.ledger().timestamp(),
+        deadline: env.ledger().timestamp() + window,
     };
 
-    // Store transfer
-    env.storage()
-        .instance()
-        .set(&DataKey::Transfer(transfer_id), &transfer);
-    env.storage().instance().set(
-        &DataKey::Settlement(transfer_id),
-        &SettlementStatus::Pending,
-    );
-
-    // Add to transfer history
-    let mut history: Vec<TransferRequest> = env
-        .storage()
-        .instance()
-        .get(&DataKey::TransferHistory)
-        .unwrap_or_else(|| Vec::new(&env));
-    history.push_back(transfer.clone());
-    env.storage()
-        .instance()
-        .set(&DataKey::TransferHistory, &history);
+    // Store transfer and index it for both parties
+    save_transfer(&env, &transfer);
+    history::record_transfer(&env, &transfer);
 
     env.events().publish(
         (Symbol::new(&env, "TransferInitiated"),),
@@ -56,8 +71,11 @@ pub fn initiate_transfer(
             transfer.sender,
             transfer.recipient,
             transfer.amount,
+            transfer.fee,
             transfer.currency,
+            transfer.target_currency,
             transfer.destination_network,
+            transfer.deadline,
         ),
     );
 
@@ -65,12 +83,22 @@ pub fn initiate_transfer(
 }
 
 pub fn get_transfer_details(env: Env, transfer_id: u64) -> TransferRequest {
+    load_transfer(&env, transfer_id)
+}
+
+pub fn load_transfer(env: &Env, transfer_id: u64) -> TransferRequest {
     env.storage()
-        .instance()
+        .persistent()
         .get(&DataKey::Transfer(transfer_id))
         .unwrap_or_else(|| panic!("Transfer not found"))
 }
 
+pub fn save_transfer(env: &Env, transfer: &TransferRequest) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::Transfer(transfer.id), transfer);
+}
+
 fn next_transfer_id(env: &Env) -> u64 {
     let transfer_id = env
         .storage()
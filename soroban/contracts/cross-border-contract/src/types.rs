@@ -1,33 +1,147 @@
-use soroban_sdk::{contracttype, Address, BytesN, String};
+use soroban_sdk::{contracterror, contracttype, Address, BytesN, String, Vec};
 
 #[contracttype]
 pub enum DataKey {
-    Transfer(u64),                // Transfer ID -> TransferRequest
-    Compliance(Address),          // Address -> ComplianceData
-    Settlement(u64),              // Transfer ID -> SettlementStatus
-    Fees,                         // FeeStructure
-    ExchangeRate(String, String), // (Source Currency, Target Currency) -> ExchangeRate
-    NextTransferId,               // Counter for transfer IDs
-    TransferHistory,              // List of all transfers
+    Transfer(u64),                        // Transfer ID -> TransferRequest
+    Compliance(Address),                  // Address -> ComplianceData
+    Fees,                                 // Default FeeStructure for unscheduled corridors
+    FeeSchedule(String, String, String),  // Corridor (Source, Target, Network) -> FeeSchedule
+    FeePromotion(String, String, String), // Corridor (Source, Target, Network) -> FeePromotion
+    Treasury,                             // Receives fees collected at settlement
+    ExchangeRate(String, String),         // (Source Currency, Target Currency) -> ExchangeRate
+    NextTransferId,                       // Counter for transfer IDs
+    SenderTransferCount(Address),         // Sender -> number of indexed transfers
+    SenderTransfer(Address, u32),         // (Sender, Index) -> Transfer ID
+    RecipientTransferCount(Address),      // Recipient -> number of indexed transfers
+    RecipientTransfer(Address, u32),      // (Recipient, Index) -> Transfer ID
+    Admin,                                // Contract administrator
+    CurrencyToken(String),                // Currency code -> token contract address
+    Liquidity(String),                    // Currency code -> reserve available for payouts
+    RatePublisher(Address),               // Address -> authorized to publish FX rates
+    MaxRateAge,                           // Seconds after which a published rate is stale
+    FxSpread(String, String),             // (Source Currency, Target Currency) -> spread bps
+    ComplianceOfficer(Address),           // Officer -> organization the officer reviews for
+    TierLimits(RiskTier),                 // Risk tier -> TierLimits
+    VolumeLog(Address),                   // Sender -> settled volume within the monthly window
+    ComplianceAdmin,                      // Manages the sanctions denylist
+    DeniedAddress(Address),               // Denylisted address
+    DeniedIdentifier(BytesN<32>),         // Denylisted identity hash
+    DenylistInfo,                         // Current DenylistInfo
+    BatchSettlement(u64),                 // Batch ID -> BatchSettlement
+    NextBatchId,                          // Counter for batch settlement IDs
+    DefaultSettlementWindow,              // Seconds a transfer may wait for settlement by default
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ContractError {
+    TreasuryNotConfigured = 1,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TransferParams {
+    pub recipient: Address,
+    pub amount: i128,
+    pub currency: String,        // Source currency, debited from the sender
+    pub target_currency: String, // Currency paid out to the recipient
+    pub destination_network: String,
+    pub is_urgent: bool,
+    pub settlement_window: u64, // Seconds until the sender may reclaim, 0 for the default
 }
 
 #[contracttype]
 #[derive(Clone)]
 pub struct TransferRequest {
+    pub id: u64,
+    pub status: SettlementStatus,
     pub sender: Address,
     pub recipient: Address,
     pub amount: i128,
     pub currency: String,
+    pub target_currency: String,
     pub destination_network: String,
+    pub fee: i128,           // Locked on top of amount, in the source currency
+    pub payout_amount: i128, // Target currency amount paid at settlement
     pub timestamp: u64,
+    pub deadline: u64, // Settlement deadline, after which the sender may reclaim the escrow
+}
+
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+pub enum ComplianceStatus {
+    Approved,
+    Rejected,
+}
+
+#[contracttype]
+#[derive(Clone, PartialEq, Debug)]
+pub enum RiskTier {
+    Low,
+    Medium,
+    High,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TransferFilter {
+    pub statuses: Vec<SettlementStatus>, // Empty matches any status
+    pub from_timestamp: Option<u64>,     // Inclusive
+    pub to_timestamp: Option<u64>,       // Inclusive
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct TransferPage {
+    pub transfers: Vec<TransferRequest>,
+    pub next_offset: Option<u32>, // Index position to resume from, None once the history is exhausted
 }
 
 #[contracttype]
 #[derive(Clone)]
 pub struct ComplianceData {
-    pub kyc_verified: bool,
-    pub aml_verified: bool,
+    pub status: ComplianceStatus,
+    pub risk_tier: RiskTier,
     pub verification_documents: BytesN<32>, // Hash of KYC/AML docs
+    pub identity_hash: BytesN<32>,          // Hashed identifier screened against the denylist
+    pub officer: Address,                   // Officer who made the decision
+    pub organization: Address,              // Organization the officer reviews for
+    pub reviewed_at: u64,
+    pub expires_at: u64,
+}
+
+// Limits are expressed in source currency units of the transfer amount
+#[contracttype]
+#[derive(Clone)]
+pub struct TierLimits {
+    pub max_transfer: i128,
+    pub daily_limit: i128,   // Rolling 24 hour settled volume
+    pub monthly_limit: i128, // Rolling 30 day settled volume
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct DenylistUpdate {
+    pub add_addresses: Vec<Address>,
+    pub remove_addresses: Vec<Address>,
+    pub add_identifiers: Vec<BytesN<32>>,
+    pub remove_identifiers: Vec<BytesN<32>>,
+    pub merkle_root: BytesN<32>, // Root of the full off-chain list for this version
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DenylistInfo {
+    pub version: u32,
+    pub merkle_root: BytesN<32>,
+    pub updated_at: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct VolumeEntry {
+    pub timestamp: u64,
+    pub amount: i128,
 }
 
 #[contracttype]
@@ -38,6 +152,35 @@ pub enum SettlementStatus {
     Settled,
     Refunded,
     Rejected,
+    Expired, // Reclaimed by the sender after the settlement deadline
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetFlow {
+    pub currency: String,
+    pub amount: i128, // Positive when org_a owes org_b
+}
+
+// Informational net position of a batch; transfers themselves always settle gross
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetSettlement {
+    pub org_a: Address, // Lower address of the counterparty pair
+    pub org_b: Address,
+    pub flows: Vec<NetFlow>, // One net amount per source currency
+    pub transfer_ids: Vec<u64>,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct BatchSettlement {
+    pub id: u64,
+    pub org: Address, // Organization that submitted the batch
+    pub settled: Vec<u64>,
+    pub rejected: Vec<u64>,
+    pub net_settlements: Vec<NetSettlement>,
+    pub timestamp: u64,
 }
 
 #[contracttype]
@@ -48,6 +191,32 @@ pub struct FeeStructure {
     pub urgency_multiplier: u32, // Multiplier for urgent transfers
 }
 
+#[contracttype]
+#[derive(Clone)]
+pub struct FeeTier {
+    pub min_volume: i128, // Sender's 30-day volume, including the transfer, at which the tier applies
+    pub percentage: u32,  // Basis points
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FeeSchedule {
+    pub base_fee: i128,
+    pub tiers: Vec<FeeTier>, // Ascending by min_volume, starting at zero
+    pub min_fee: i128,
+    pub max_fee: i128,
+    pub urgency_multiplier: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct FeePromotion {
+    pub base_fee: i128,
+    pub percentage: u32, // Basis points
+    pub starts_at: u64,
+    pub ends_at: u64, // Exclusive
+}
+
 #[contracttype]
 #[derive(Clone)]
 pub struct ExchangeRate {
@@ -55,7 +224,28 @@ pub struct ExchangeRate {
     pub target_currency: String,
     pub rate: i128, // Fixed-point rate scaled by RATE_SCALE
     pub timestamp: u64,
+    pub publisher: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct FxQuote {
+    pub mid: i128,
+    pub bid: i128, // Rate paid to customers converting source into target
+    pub ask: i128,
+    pub spread_bps: u32,
 }
 
 // Scale factor for fixed-point exchange rates (e.g., 1,000,000 = 6 decimal places)
 pub const RATE_SCALE: i128 = 1_000_000;
+
+// Rolling windows used for transfer volume limits, in seconds
+pub const DAY_SECONDS: u64 = 86_400;
+pub const MONTH_SECONDS: u64 = 30 * DAY_SECONDS;
+
+// Default maximum age of a published exchange rate, in seconds
+pub const DEFAULT_MAX_RATE_AGE: u64 = 3_600;
+
+// Settlement windows: the default applied when a transfer does not set one, and the longest allowed
+pub const DEFAULT_SETTLEMENT_WINDOW: u64 = 3 * DAY_SECONDS;
+pub const MAX_SETTLEMENT_WINDOW: u64 = MONTH_SECONDS;
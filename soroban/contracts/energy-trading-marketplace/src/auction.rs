@@ -0,0 +1,356 @@
+use crate::escrow;
+use crate::storage;
+use crate::trading;
+use crate::utils::*;
+use soroban_sdk::{symbol_short, xdr::ToXdr, Address, BytesN, Env, Map, Vec};
+
+/// Open a sealed-bid auction for a delivery slot
+pub fn open_auction(
+    env: &Env,
+    operator: Address,
+    delivery_slot: u64,
+    bidding_closes_at: u64,
+    reveal_closes_at: u64,
+) -> Result<(), MarketplaceError> {
+    let grid_operators: Map<Address, bool> = env
+        .storage()
+        .instance()
+        .get(&DataKey::GridOperators)
+        .unwrap_or_else(|| Map::new(env));
+    if !grid_operators.contains_key(operator.clone()) {
+        return Err(MarketplaceError::NotAuthorized);
+    }
+
+    trading::validate_delivery_slot(env, delivery_slot)?;
+    if has_auction(env, delivery_slot) {
+        return Err(MarketplaceError::SlotInAuction);
+    }
+
+    // Both phases must finish before delivery starts
+    let now = env.ledger().timestamp();
+    if bidding_closes_at <= now
+        || reveal_closes_at <= bidding_closes_at
+        || reveal_closes_at > delivery_slot
+    {
+        return Err(MarketplaceError::InvalidInput);
+    }
+
+    // A slot already trading continuously cannot switch to an auction
+    if !trading::get_book(env, &OrderType::Buy, delivery_slot).is_empty()
+        || !trading::get_book(env, &OrderType::Sell, delivery_slot).is_empty()
+    {
+        return Err(MarketplaceError::InvalidInput);
+    }
+
+    let auction = Auction {
+        delivery_slot,
+        operator: operator.clone(),
+        bidding_closes_at,
+        reveal_closes_at,
+        sealed_bids: Vec::new(env),
+        orders: Vec::new(env),
+        status: AuctionStatus::Open,
+        clearing_price: 0,
+        cleared_quantity: 0,
+    };
+    save_auction(env, &auction);
+
+    env.events().publish(
+        (symbol_short!("auction"), delivery_slot),
+        (operator, bidding_closes_at, reveal_closes_at),
+    );
+
+    Ok(())
+}
+
+/// Submit a commitment to an order with a deposit large enough to back it
+pub fn submit_sealed_order(
+    env: &Env,
+    trader: Address,
+    delivery_slot: u64,
+    commitment: BytesN<32>,
+    deposit: u64,
+) -> Result<u64, MarketplaceError> {
+    let mut auction = get_auction(env, delivery_slot)?;
+    if auction.status != AuctionStatus::Open
+        || env.ledger().timestamp() >= auction.bidding_closes_at
+    {
+        return Err(MarketplaceError::AuctionPhaseError);
+    }
+    if deposit == 0 {
+        return Err(MarketplaceError::InvalidInput);
+    }
+
+    escrow::lock(env, &trader, deposit)?;
+
+    let bid_id = get_next_sealed_bid_id(env);
+    let sealed_bid = SealedBid {
+        bid_id,
+        trader,
+        delivery_slot,
+        commitment,
+        deposit,
+        revealed: false,
+    };
+    storage::set_persistent(env, &MarketKey::SealedBid(bid_id), &sealed_bid);
+
+    auction.sealed_bids.push_back(bid_id);
+    save_auction(env, &auction);
+
+    Ok(bid_id)
+}
+
+/// Reveal a sealed order, turning it into an auction order backed by part of the deposit
+pub fn reveal_sealed_order(
+    env: &Env,
+    trader: Address,
+    bid_id: u64,
+    order_type: OrderType,
+    quantity_kwh: u64,
+    price_per_kwh: u64,
+    salt: BytesN<32>,
+) -> Result<u64, MarketplaceError> {
+    let key = MarketKey::SealedBid(bid_id);
+    let mut sealed_bid: SealedBid =
+        storage::get_persistent(env, &key).ok_or(MarketplaceError::OrderNotFound)?;
+    if sealed_bid.trader != trader {
+        return Err(MarketplaceError::NotAuthorized);
+    }
+
+    let mut auction = get_auction(env, sealed_bid.delivery_slot)?;
+    let now = env.ledger().timestamp();
+    if sealed_bid.revealed
+        || auction.status != AuctionStatus::Open
+        || now < auction.bidding_closes_at
+        || now >= auction.reveal_closes_at
+    {
+        return Err(MarketplaceError::AuctionPhaseError);
+    }
+
+    let expected = compute_commitment(
+        env,
+        &trader,
+        sealed_bid.delivery_slot,
+        &order_type,
+        quantity_kwh,
+        price_per_kwh,
+        &salt,
+    );
+    if sealed_bid.commitment != expected {
+        return Err(MarketplaceError::CommitmentMismatch);
+    }
+
+    let collateral = escrow::required_collateral(env, &order_type, quantity_kwh, price_per_kwh);
+    if collateral > sealed_bid.deposit {
+        return Err(MarketplaceError::PaymentFailed);
+    }
+
+    // A trader cannot sit on both sides of the same auction
+    for order_id in auction.orders.iter() {
+        let order = trading::get_order(env, order_id)?;
+        if order.trader == trader && order.order_type != order_type {
+            return Err(MarketplaceError::InvalidInput);
+        }
+    }
+
+    escrow::release(env, &trader, sealed_bid.deposit - collateral)?;
+    sealed_bid.revealed = true;
+    storage::set_persistent(env, &key, &sealed_bid);
+
+    let order = trading::new_order(
+        env,
+        trader,
+        order_type,
+        quantity_kwh,
+        price_per_kwh,
+        sealed_bid.delivery_slot,
+        collateral,
+    );
+    storage::save_order(env, &order);
+    let order_id = order.order_id;
+    auction.orders.push_back(order_id);
+    save_auction(env, &auction);
+
+    Ok(order_id)
+}
+
+/// Clear an auction at a single price once the reveal phase is over
+///
+/// Bids are ranked highest first and offers lowest first. The matched volume is the largest
+/// quantity for which bids still meet offers, and every match trades at the price of the
+/// marginal (highest accepted) offer. Unmatched quantity is cancelled and its escrow released.
+pub fn clear_auction(env: &Env, delivery_slot: u64) -> Result<Auction, MarketplaceError> {
+    let mut auction = get_auction(env, delivery_slot)?;
+    if auction.status != AuctionStatus::Open || env.ledger().timestamp() < auction.reveal_closes_at
+    {
+        return Err(MarketplaceError::AuctionPhaseError);
+    }
+
+    let mut bids = Vec::new(env);
+    let mut asks = Vec::new(env);
+    for order_id in auction.orders.iter() {
+        let order = storage::load_order(env, order_id)?;
+        match order.order_type {
+            OrderType::Buy => insert_ranked(&mut bids, order),
+            OrderType::Sell => insert_ranked(&mut asks, order),
+        }
+    }
+
+    // First pass finds the marginal offer, second pass executes at that price
+    let clearing_price = marginal_price(&bids, &asks);
+    let mut cleared_quantity = 0;
+    if clearing_price > 0 {
+        let mut bid_index = 0;
+        let mut ask_index = 0;
+        while bid_index < bids.len() && ask_index < asks.len() {
+            let mut bid = bids.get(bid_index).unwrap();
+            let mut ask = asks.get(ask_index).unwrap();
+            if bid.price_per_kwh < ask.price_per_kwh {
+                break;
+            }
+            let quantity = trading::remaining(&bid).min(trading::remaining(&ask));
+            trading::execute_trade(env, &mut bid, &mut ask, quantity, clearing_price)?;
+            cleared_quantity += quantity;
+
+            bids.set(bid_index, bid.clone());
+            asks.set(ask_index, ask.clone());
+            if bid.status != OrderStatus::Active {
+                bid_index += 1;
+            }
+            if ask.status != OrderStatus::Active {
+                ask_index += 1;
+            }
+        }
+    }
+
+    // Whatever did not clear is cancelled and its collateral returned
+    for mut order in bids.iter().chain(asks.iter()) {
+        if order.status == OrderStatus::Active {
+            escrow::release(env, &order.trader, order.escrow_amount)?;
+            order.escrow_amount = 0;
+            order.status = OrderStatus::Cancelled;
+        }
+        storage::save_order(env, &order);
+    }
+
+    // Deposits behind commitments that were never revealed are returned as well; sealed bids
+    // are no longer needed once the auction clears
+    for bid_id in auction.sealed_bids.iter() {
+        let key = MarketKey::SealedBid(bid_id);
+        let sealed_bid: SealedBid = storage::get_persistent(env, &key).unwrap();
+        if !sealed_bid.revealed {
+            escrow::release(env, &sealed_bid.trader, sealed_bid.deposit)?;
+        }
+        env.storage().persistent().remove(&key);
+    }
+
+    auction.sealed_bids = Vec::new(env);
+    auction.status = AuctionStatus::Cleared;
+    auction.clearing_price = clearing_price;
+    auction.cleared_quantity = cleared_quantity;
+    save_auction(env, &auction);
+
+    env.events().publish(
+        (symbol_short!("cleared"), delivery_slot),
+        (clearing_price, cleared_quantity),
+    );
+
+    Ok(auction)
+}
+
+/// Commitment for a sealed order: sha256 of the trader's address (XDR), the delivery slot,
+/// order type, quantity and price (big-endian) followed by the trader's salt
+pub fn compute_commitment(
+    env: &Env,
+    trader: &Address,
+    delivery_slot: u64,
+    order_type: &OrderType,
+    quantity_kwh: u64,
+    price_per_kwh: u64,
+    salt: &BytesN<32>,
+) -> BytesN<32> {
+    let side: u32 = match order_type {
+        OrderType::Buy => 0,
+        OrderType::Sell => 1,
+    };
+    let mut preimage = trader.clone().to_xdr(env);
+    preimage.extend_from_array(&delivery_slot.to_be_bytes());
+    preimage.extend_from_array(&side.to_be_bytes());
+    preimage.extend_from_array(&quantity_kwh.to_be_bytes());
+    preimage.extend_from_array(&price_per_kwh.to_be_bytes());
+    preimage.append(&salt.clone().into());
+    env.crypto().sha256(&preimage).to_bytes()
+}
+
+pub fn get_auction(env: &Env, delivery_slot: u64) -> Result<Auction, MarketplaceError> {
+    storage::get_persistent(env, &MarketKey::Auction(delivery_slot))
+        .ok_or(MarketplaceError::AuctionNotFound)
+}
+
+pub fn has_auction(env: &Env, delivery_slot: u64) -> bool {
+    env.storage()
+        .persistent()
+        .has(&MarketKey::Auction(delivery_slot))
+}
+
+fn save_auction(env: &Env, auction: &Auction) {
+    storage::set_persistent(env, &MarketKey::Auction(auction.delivery_slot), auction);
+}
+
+/// Price of the highest offer that still crosses a bid when both sides are walked in rank order
+fn marginal_price(bids: &Vec<EnergyOrder>, asks: &Vec<EnergyOrder>) -> u64 {
+    let mut price = 0;
+    let mut bid_index = 0;
+    let mut ask_index = 0;
+    let mut bid_left = 0;
+    let mut ask_left = 0;
+    while bid_index < bids.len() && ask_index < asks.len() {
+        let bid = bids.get(bid_index).unwrap();
+        let ask = asks.get(ask_index).unwrap();
+        if bid.price_per_kwh < ask.price_per_kwh {
+            break;
+        }
+        if bid_left == 0 {
+            bid_left = trading::remaining(&bid);
+        }
+        if ask_left == 0 {
+            ask_left = trading::remaining(&ask);
+        }
+        price = ask.price_per_kwh;
+
+        let quantity = bid_left.min(ask_left);
+        bid_left -= quantity;
+        ask_left -= quantity;
+        if bid_left == 0 {
+            bid_index += 1;
+        }
+        if ask_left == 0 {
+            ask_index += 1;
+        }
+    }
+    price
+}
+
+/// Insert an order into a list ranked by price-time priority
+fn insert_ranked(ranked: &mut Vec<EnergyOrder>, order: EnergyOrder) {
+    let mut position = ranked.len();
+    for (index, other) in ranked.iter().enumerate() {
+        if trading::has_priority(&order, &other) {
+            position = index as u32;
+            break;
+        }
+    }
+    ranked.insert(position, order);
+}
+
+fn get_next_sealed_bid_id(env: &Env) -> u64 {
+    let current_id: u64 = env
+        .storage()
+        .instance()
+        .get(&DataKey::NextSealedBidId)
+        .unwrap_or(1);
+    env.storage()
+        .instance()
+        .set(&DataKey::NextSealedBidId, &(current_id + 1));
+    current_id
+}
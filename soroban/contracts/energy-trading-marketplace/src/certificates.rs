@@ -0,0 +1,108 @@
+use crate::utils::*;
+use soroban_sdk::{contractclient, contracttype, Address, BytesN, Env, Map, String, Vec};
+
+/// Certificate as stored by the renewable-energy-certificate-tracker contract
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rec {
+    pub id: BytesN<32>,
+    pub issuer: Address,
+    pub energy_source: EnergySource,
+    pub production_date: u64,
+    pub production_start: u64,
+    pub production_end: u64,
+    pub production_location: String,
+    pub capacity_mwh: i128,
+    pub current_owner: Address,
+    pub status: RecStatus,
+    pub verification_standard: String,
+    pub verification_hash: BytesN<32>,
+    pub issuance_date: u64,
+    pub metadata: Map<String, String>,
+    pub lineage: Vec<BytesN<32>>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnergySource {
+    Solar,
+    Wind,
+    Hydro,
+    Geothermal,
+    Biomass,
+    Tidal,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecStatus {
+    Issued,
+    Transferred,
+    Retired,
+    Suspended,
+}
+
+/// The parts of the certificate tracker's interface the marketplace calls; only the generated
+/// client is used directly
+#[allow(dead_code)]
+#[contractclient(name = "RecTrackerClient")]
+pub trait RecTracker {
+    fn get_rec_status(env: Env, rec_id: BytesN<32>) -> Rec;
+    fn transfer_rec(
+        env: Env,
+        rec_id: BytesN<32>,
+        from: Address,
+        to: Address,
+        capacity_mwh: i128,
+    ) -> BytesN<32>;
+}
+
+/// Check that a seller owns a usable certificate covering `quantity_kwh` and take it into custody.
+/// Returns the certificate the marketplace now holds, which the tracker splits off for partial
+/// amounts
+pub fn lock(
+    env: &Env,
+    seller: &Address,
+    rec_id: &BytesN<32>,
+    quantity_kwh: u64,
+) -> Result<BytesN<32>, MarketplaceError> {
+    if !quantity_kwh.is_multiple_of(KWH_PER_MWH) {
+        return Err(MarketplaceError::InvalidInput);
+    }
+    let mwh = (quantity_kwh / KWH_PER_MWH) as i128;
+
+    let client = RecTrackerClient::new(env, &get_rec_contract(env)?);
+    let rec = client.get_rec_status(rec_id);
+    if rec.current_owner != *seller
+        || rec.status == RecStatus::Retired
+        || rec.status == RecStatus::Suspended
+        || rec.capacity_mwh < mwh
+    {
+        return Err(MarketplaceError::CertificateInvalid);
+    }
+
+    Ok(client.transfer_rec(rec_id, seller, &env.current_contract_address(), &mwh))
+}
+
+/// Hand certificate MWh held in custody to `to`
+pub fn release(
+    env: &Env,
+    rec_id: &BytesN<32>,
+    to: &Address,
+    quantity_kwh: u64,
+) -> Result<(), MarketplaceError> {
+    let mwh = (quantity_kwh / KWH_PER_MWH) as i128;
+    if mwh == 0 {
+        return Ok(());
+    }
+    let client = RecTrackerClient::new(env, &get_rec_contract(env)?);
+    client.transfer_rec(rec_id, &env.current_contract_address(), to, &mwh);
+    Ok(())
+}
+
+fn get_rec_contract(env: &Env) -> Result<Address, MarketplaceError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::RecContract)
+        .ok_or(MarketplaceError::RecContractNotSet)
+}
@@ -0,0 +1,61 @@
+use crate::utils::*;
+use soroban_sdk::{token, Address, Env};
+
+/// Lock tokens from a trader into the marketplace
+pub fn lock(env: &Env, from: &Address, amount: u64) -> Result<(), MarketplaceError> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let token_client = token::Client::new(env, &get_token_contract(env)?);
+    if token_client.balance(from) < amount as i128 {
+        return Err(MarketplaceError::PaymentFailed);
+    }
+    token_client.transfer(from, &env.current_contract_address(), &(amount as i128));
+    Ok(())
+}
+
+/// Release escrowed tokens from the marketplace
+pub fn release(env: &Env, to: &Address, amount: u64) -> Result<(), MarketplaceError> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let token_client = token::Client::new(env, &get_token_contract(env)?);
+    token_client.transfer(&env.current_contract_address(), to, &(amount as i128));
+    Ok(())
+}
+
+/// Tokens an order must lock: the full value for buys, the delivery bond for sells
+pub fn required_collateral(
+    env: &Env,
+    order_type: &OrderType,
+    quantity_kwh: u64,
+    price_per_kwh: u64,
+) -> u64 {
+    let value = quantity_kwh * price_per_kwh;
+    match order_type {
+        OrderType::Buy => value,
+        OrderType::Sell => value * get_seller_bond_rate(env) as u64 / 10_000,
+    }
+}
+
+/// Take the share of an order's escrow that backs `quantity` of its unfilled amount
+pub fn take_share(order: &mut EnergyOrder, quantity: u64) -> u64 {
+    let unfilled = order.quantity_kwh - order.filled_quantity;
+    let share = order.escrow_amount * quantity / unfilled;
+    order.escrow_amount -= share;
+    share
+}
+
+pub fn get_seller_bond_rate(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::SellerBondRate)
+        .unwrap_or(DEFAULT_SELLER_BOND_BPS)
+}
+
+fn get_token_contract(env: &Env) -> Result<Address, MarketplaceError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::TokenContract)
+        .ok_or(MarketplaceError::PaymentFailed)
+}
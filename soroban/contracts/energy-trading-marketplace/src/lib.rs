@@ -1,13 +1,17 @@
 #![no_std]
 
+mod auction;
+mod certificates;
+mod escrow;
 mod settlement;
+mod storage;
 mod trading;
 mod utils;
 
 #[cfg(test)]
 mod tests;
 
-use soroban_sdk::{contract, contractimpl, Address, Env, Map, Vec};
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Map, Vec};
 
 use crate::utils::*;
 
@@ -46,8 +50,6 @@ impl EnergyTradingMarketplace {
         let producers: Map<Address, bool> = Map::new(&env);
         let consumers: Map<Address, bool> = Map::new(&env);
         let grid_operators: Map<Address, bool> = Map::new(&env);
-        let orders: Map<u64, EnergyOrder> = Map::new(&env);
-        let trades: Map<u64, Trade> = Map::new(&env);
 
         env.storage()
             .instance()
@@ -58,8 +60,6 @@ impl EnergyTradingMarketplace {
         env.storage()
             .instance()
             .set(&DataKey::GridOperators, &grid_operators);
-        env.storage().instance().set(&DataKey::Orders, &orders);
-        env.storage().instance().set(&DataKey::Trades, &trades);
 
         Ok(())
     }
@@ -102,6 +102,55 @@ impl EnergyTradingMarketplace {
         Ok(())
     }
 
+    /// Register a grid operator
+    pub fn register_grid_operator(env: Env, operator: Address) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        let mut grid_operators: Map<Address, bool> = env
+            .storage()
+            .instance()
+            .get(&DataKey::GridOperators)
+            .unwrap_or_else(|| Map::new(&env));
+
+        grid_operators.set(operator, true);
+        env.storage()
+            .instance()
+            .set(&DataKey::GridOperators, &grid_operators);
+
+        Ok(())
+    }
+
+    /// Set the renewable-energy-certificate-tracker contract that backs certified sell orders
+    pub fn set_rec_contract(env: Env, rec_contract: Address) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        env.storage()
+            .instance()
+            .set(&DataKey::RecContract, &rec_contract);
+
+        Ok(())
+    }
+
+    /// Set the seller delivery bond as basis points of the order value
+    pub fn set_seller_bond_rate(env: Env, bond_bps: u32) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+        admin.require_auth();
+
+        if bond_bps > 10_000 {
+            return Err(MarketplaceError::InvalidInput);
+        }
+        env.storage()
+            .instance()
+            .set(&DataKey::SellerBondRate, &bond_bps);
+
+        Ok(())
+    }
+
     /// Place an energy order
     pub fn place_order(
         env: Env,
@@ -109,14 +158,195 @@ impl EnergyTradingMarketplace {
         order_type: OrderType,
         quantity_kwh: u64,
         price_per_kwh: u64,
+        delivery_slot: u64,
+        time_in_force: TimeInForce,
+    ) -> Result<u64, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        trader.require_auth();
+
+        Self::validate_trader_registration(&env, &trader)?;
+        Self::validate_order_params(quantity_kwh, price_per_kwh)?;
+
+        let mut order = trading::new_order(
+            &env,
+            trader,
+            order_type,
+            quantity_kwh,
+            price_per_kwh,
+            delivery_slot,
+            0,
+        );
+        order.time_in_force = time_in_force;
+        trading::place_order(&env, order)
+    }
+
+    /// Place a sell order backed by a renewable energy certificate, asking a green premium per kWh
+    #[allow(clippy::too_many_arguments)]
+    pub fn place_certified_sell_order(
+        env: Env,
+        trader: Address,
+        quantity_kwh: u64,
+        price_per_kwh: u64,
+        delivery_slot: u64,
+        time_in_force: TimeInForce,
+        rec_id: BytesN<32>,
+        green_premium: u64,
+    ) -> Result<u64, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        trader.require_auth();
+
+        Self::validate_trader_registration(&env, &trader)?;
+        Self::validate_order_params(quantity_kwh, price_per_kwh)?;
+
+        let mut order = trading::new_order(
+            &env,
+            trader,
+            OrderType::Sell,
+            quantity_kwh,
+            price_per_kwh,
+            delivery_slot,
+            0,
+        );
+        order.time_in_force = time_in_force;
+        order.origin = EnergyOrigin::Certified(rec_id);
+        order.green_premium = green_premium;
+        trading::place_order(&env, order)
+    }
+
+    /// Place a buy order that only matches certified supply, paying at most `max_green_premium` per kWh
+    pub fn place_green_buy_order(
+        env: Env,
+        trader: Address,
+        quantity_kwh: u64,
+        price_per_kwh: u64,
+        delivery_slot: u64,
+        time_in_force: TimeInForce,
+        max_green_premium: u64,
+    ) -> Result<u64, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        trader.require_auth();
+
+        Self::validate_trader_registration(&env, &trader)?;
+        Self::validate_order_params(quantity_kwh, price_per_kwh)?;
+
+        let mut order = trading::new_order(
+            &env,
+            trader,
+            OrderType::Buy,
+            quantity_kwh,
+            price_per_kwh,
+            delivery_slot,
+            0,
+        );
+        order.time_in_force = time_in_force;
+        order.green_only = true;
+        order.green_premium = max_green_premium;
+        trading::place_order(&env, order)
+    }
+
+    /// Expire stale orders in a page of one side of a slot's book and refund their escrow
+    pub fn prune_expired_orders(
+        env: Env,
+        order_type: OrderType,
+        delivery_slot: u64,
+        offset: u32,
+        limit: u32,
+    ) -> Result<u32, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        trading::prune_expired_orders(&env, order_type, delivery_slot, offset, limit)
+    }
+
+    /// Open a sealed-bid auction for a delivery slot
+    pub fn open_auction(
+        env: Env,
+        operator: Address,
+        delivery_slot: u64,
+        bidding_closes_at: u64,
+        reveal_closes_at: u64,
+    ) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        operator.require_auth();
+
+        auction::open_auction(
+            &env,
+            operator,
+            delivery_slot,
+            bidding_closes_at,
+            reveal_closes_at,
+        )
+    }
+
+    /// Submit a sealed order commitment backed by a deposit
+    pub fn submit_sealed_order(
+        env: Env,
+        trader: Address,
+        delivery_slot: u64,
+        commitment: BytesN<32>,
+        deposit: u64,
     ) -> Result<u64, MarketplaceError> {
         Self::check_initialized(&env)?;
         trader.require_auth();
 
         Self::validate_trader_registration(&env, &trader)?;
+        auction::submit_sealed_order(&env, trader, delivery_slot, commitment, deposit)
+    }
+
+    /// Reveal a sealed order during the reveal phase
+    pub fn reveal_sealed_order(
+        env: Env,
+        trader: Address,
+        bid_id: u64,
+        order_type: OrderType,
+        quantity_kwh: u64,
+        price_per_kwh: u64,
+        salt: BytesN<32>,
+    ) -> Result<u64, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        trader.require_auth();
+
         Self::validate_order_params(quantity_kwh, price_per_kwh)?;
+        auction::reveal_sealed_order(
+            &env,
+            trader,
+            bid_id,
+            order_type,
+            quantity_kwh,
+            price_per_kwh,
+            salt,
+        )
+    }
 
-        trading::place_order(&env, trader, order_type, quantity_kwh, price_per_kwh)
+    /// Clear an auction at a uniform price once reveals have closed
+    pub fn clear_auction(env: Env, delivery_slot: u64) -> Result<Auction, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        auction::clear_auction(&env, delivery_slot)
+    }
+
+    /// Get auction details
+    pub fn get_auction(env: Env, delivery_slot: u64) -> Result<Auction, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        auction::get_auction(&env, delivery_slot)
+    }
+
+    /// Compute the commitment for a trader's sealed order in a delivery slot
+    pub fn compute_commitment(
+        env: Env,
+        trader: Address,
+        delivery_slot: u64,
+        order_type: OrderType,
+        quantity_kwh: u64,
+        price_per_kwh: u64,
+        salt: BytesN<32>,
+    ) -> BytesN<32> {
+        auction::compute_commitment(
+            &env,
+            &trader,
+            delivery_slot,
+            &order_type,
+            quantity_kwh,
+            price_per_kwh,
+            &salt,
+        )
     }
 
     /// Cancel an order
@@ -127,6 +357,33 @@ impl EnergyTradingMarketplace {
         trading::cancel_order(&env, trader, order_id)
     }
 
+    /// Bind the meter that attests a producer's deliveries (admin or grid operator only)
+    pub fn register_meter(
+        env: Env,
+        operator: Address,
+        producer: Address,
+        meter: Address,
+    ) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        operator.require_auth();
+
+        Self::validate_trader_registration(&env, &producer)?;
+        settlement::register_meter(&env, operator, producer, meter)
+    }
+
+    /// Attest the kWh delivered for a trade
+    pub fn attest_delivery(
+        env: Env,
+        attester: Address,
+        trade_id: u64,
+        delivered_kwh: u64,
+    ) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        attester.require_auth();
+
+        settlement::attest_delivery(&env, attester, trade_id, delivered_kwh)
+    }
+
     /// Settle a trade
     pub fn settle_trade(env: Env, trade_id: u64, settler: Address) -> Result<(), MarketplaceError> {
         Self::check_initialized(&env)?;
@@ -135,22 +392,123 @@ impl EnergyTradingMarketplace {
         settlement::settle_trade(&env, trade_id, settler)
     }
 
+    /// Reclaim the payment and seller bond of a trade left unattested past its deadline
+    pub fn reclaim_unattested_trade(
+        env: Env,
+        trade_id: u64,
+        buyer: Address,
+    ) -> Result<(), MarketplaceError> {
+        Self::check_initialized(&env)?;
+        buyer.require_auth();
+
+        settlement::reclaim_unattested_trade(&env, trade_id, buyer)
+    }
+
     /// Get order details
     pub fn get_order(env: Env, order_id: u64) -> Result<EnergyOrder, MarketplaceError> {
         Self::check_initialized(&env)?;
         trading::get_order(&env, order_id)
     }
 
+    /// Get resting orders on one side of the book, best price first
+    pub fn get_order_book(
+        env: Env,
+        order_type: OrderType,
+        delivery_slot: u64,
+        limit: u32,
+    ) -> Result<Vec<EnergyOrder>, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(trading::get_order_book(
+            &env,
+            order_type,
+            delivery_slot,
+            limit,
+        ))
+    }
+
     /// Get trade details
     pub fn get_trade(env: Env, trade_id: u64) -> Result<Trade, MarketplaceError> {
         Self::check_initialized(&env)?;
         settlement::get_trade(&env, trade_id)
     }
 
-    /// Get trade history for a trader
-    pub fn get_trade_history(env: Env, trader: Address) -> Result<Vec<Trade>, MarketplaceError> {
+    /// Get a page of the trades a trader bought or sold in, oldest first
+    pub fn get_trade_history(
+        env: Env,
+        trader: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Trade>, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(storage::list_trades(
+            &env,
+            &Index::TraderTrades(trader),
+            offset,
+            limit,
+        ))
+    }
+
+    /// Get a page of a trader's orders, oldest first
+    pub fn get_orders_by_trader(
+        env: Env,
+        trader: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<EnergyOrder>, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(storage::list_orders(
+            &env,
+            &Index::TraderOrders(trader),
+            offset,
+            limit,
+        ))
+    }
+
+    /// Get a page of the orders with a given status, in no particular order
+    ///
+    /// Status changes reorder the index, so paging while orders change status may skip or
+    /// repeat entries.
+    pub fn get_orders_by_status(
+        env: Env,
+        status: OrderStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<EnergyOrder>, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(storage::list_orders(
+            &env,
+            &Index::OrderStatus(status),
+            offset,
+            limit,
+        ))
+    }
+
+    /// Get a page of the trades with a given status, in no particular order
+    pub fn get_trades_by_status(
+        env: Env,
+        status: TradeStatus,
+        offset: u32,
+        limit: u32,
+    ) -> Result<Vec<Trade>, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(storage::list_trades(
+            &env,
+            &Index::TradeStatus(status),
+            offset,
+            limit,
+        ))
+    }
+
+    /// Get the number of orders placed by a trader
+    pub fn get_trader_order_count(env: Env, trader: Address) -> Result<u32, MarketplaceError> {
+        Self::check_initialized(&env)?;
+        Ok(storage::index_len(&env, &Index::TraderOrders(trader)))
+    }
+
+    /// Get the number of trades a trader took part in
+    pub fn get_trader_trade_count(env: Env, trader: Address) -> Result<u32, MarketplaceError> {
         Self::check_initialized(&env)?;
-        get_trades_by_trader(&env, trader)
+        Ok(storage::index_len(&env, &Index::TraderTrades(trader)))
     }
 
     fn check_initialized(env: &Env) -> Result<(), MarketplaceError> {
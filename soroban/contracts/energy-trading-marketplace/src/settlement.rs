@@ -1,67 +1,205 @@
+use crate::certificates;
+use crate::escrow;
+use crate::storage;
 use crate::utils::*;
-use soroban_sdk::{symbol_short, token, Address, Env, Map};
+use soroban_sdk::{symbol_short, Address, Env, Map};
 
-/// Settle a trade with payment transfer
-pub fn settle_trade(env: &Env, trade_id: u64, settler: Address) -> Result<(), MarketplaceError> {
-    let trades: Map<u64, Trade> = env
+/// Record the energy actually delivered for a trade once its delivery slot has ended
+///
+/// Only the seller's registered meter or a grid operator can attest, only once, and only
+/// within the attestation window after the slot.
+pub fn attest_delivery(
+    env: &Env,
+    attester: Address,
+    trade_id: u64,
+    delivered_kwh: u64,
+) -> Result<(), MarketplaceError> {
+    let mut trade = storage::load_trade(env, trade_id)?;
+
+    if !is_delivery_attester(env, &attester, &trade.seller) {
+        return Err(MarketplaceError::NotAuthorized);
+    }
+
+    if trade.status != TradeStatus::Pending {
+        return Err(MarketplaceError::TradeAlreadySettled);
+    }
+
+    let now = env.ledger().timestamp();
+    if now < trade.delivery_slot + SLOT_SECONDS {
+        return Err(MarketplaceError::DeliveryNotFinished);
+    }
+    if now >= attestation_deadline(&trade) {
+        return Err(MarketplaceError::AttestationWindowClosed);
+    }
+
+    if delivered_kwh > trade.quantity_kwh {
+        return Err(MarketplaceError::QuantityOutOfRange);
+    }
+
+    trade.delivered_kwh = delivered_kwh;
+    trade.status = TradeStatus::Delivered;
+    storage::save_trade(env, &trade);
+
+    env.events().publish(
+        (symbol_short!("delivered"), trade_id),
+        (attester, delivered_kwh),
+    );
+
+    Ok(())
+}
+
+/// Bind the meter that attests deliveries for a producer
+///
+/// Only the admin or a grid operator can bind meters, so a producer cannot attest its own
+/// deliveries through a meter it controls.
+pub fn register_meter(
+    env: &Env,
+    operator: Address,
+    producer: Address,
+    meter: Address,
+) -> Result<(), MarketplaceError> {
+    let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
+    if operator != admin && !is_grid_operator(env, &operator) {
+        return Err(MarketplaceError::NotAuthorized);
+    }
+    if meter == producer {
+        return Err(MarketplaceError::InvalidInput);
+    }
+
+    storage::set_persistent(env, &MarketKey::Meter(producer), &meter);
+    Ok(())
+}
+
+/// Get the meter registered for a producer
+pub fn get_meter(env: &Env, producer: Address) -> Option<Address> {
+    storage::get_persistent(env, &MarketKey::Meter(producer))
+}
+
+fn is_delivery_attester(env: &Env, attester: &Address, seller: &Address) -> bool {
+    get_meter(env, seller.clone()).as_ref() == Some(attester) || is_grid_operator(env, attester)
+}
+
+fn is_grid_operator(env: &Env, address: &Address) -> bool {
+    let grid_operators: Map<Address, bool> = env
         .storage()
         .instance()
-        .get(&DataKey::Trades)
+        .get(&DataKey::GridOperators)
         .unwrap_or_else(|| Map::new(env));
+    grid_operators.contains_key(address.clone())
+}
 
-    let trade = trades
-        .get(trade_id)
-        .ok_or(MarketplaceError::TradeNotFound)?;
+/// Settle a delivered trade from escrow
+///
+/// The seller is paid for the attested kWh. The buyer gets back the payment for any shortfall
+/// plus the matching share of the seller's bond as a penalty; the rest of the bond is returned.
+/// Certificate MWh backing the trade go to the buyer for whole MWh delivered.
+pub fn settle_trade(env: &Env, trade_id: u64, settler: Address) -> Result<(), MarketplaceError> {
+    let mut trade = storage::load_trade(env, trade_id)?;
 
     // Verify authorization (only buyer or seller can settle)
     if settler != trade.buyer && settler != trade.seller {
         return Err(MarketplaceError::NotAuthorized);
     }
 
+    match trade.status {
+        TradeStatus::Pending => return Err(MarketplaceError::DeliveryNotConfirmed),
+        TradeStatus::Settled | TradeStatus::Refunded => {
+            return Err(MarketplaceError::TradeAlreadySettled)
+        }
+        TradeStatus::Delivered => {}
+    }
+
+    trade.status = TradeStatus::Settled;
+    storage::save_trade(env, &trade);
+
     // Execute payment transfer
-    execute_payment(&env, &trade)?;
+    execute_payment(env, &trade)?;
 
     // Emit settlement event
     env.events().publish(
         (symbol_short!("settled"), trade_id),
-        (trade.quantity_kwh, trade.total_amount),
+        (trade.delivered_kwh, trade.total_amount),
     );
 
     Ok(())
 }
 
-/// Execute payment transfer using Stellar tokens
-fn execute_payment(env: &Env, trade: &Trade) -> Result<(), MarketplaceError> {
-    // Get token contract
-    let token_contract: Address = env
-        .storage()
-        .instance()
-        .get(&DataKey::TokenContract)
-        .ok_or(MarketplaceError::PaymentFailed)?;
+/// Refund a trade whose delivery was never attested once the attestation window has closed
+///
+/// The buyer gets the escrowed payment back together with the seller's bond, as for a trade
+/// attested with nothing delivered. Certificates backing the trade return to the seller.
+pub fn reclaim_unattested_trade(
+    env: &Env,
+    trade_id: u64,
+    buyer: Address,
+) -> Result<(), MarketplaceError> {
+    let mut trade = storage::load_trade(env, trade_id)?;
+
+    if buyer != trade.buyer {
+        return Err(MarketplaceError::NotAuthorized);
+    }
+    if trade.status != TradeStatus::Pending {
+        return Err(MarketplaceError::TradeAlreadySettled);
+    }
+    if env.ledger().timestamp() < attestation_deadline(&trade) {
+        return Err(MarketplaceError::AttestationWindowOpen);
+    }
+
+    trade.delivered_kwh = 0;
+    trade.status = TradeStatus::Refunded;
+    storage::save_trade(env, &trade);
+
+    execute_payment(env, &trade)?;
+
+    env.events().publish(
+        (symbol_short!("refunded"), trade_id),
+        (trade.total_amount, trade.bond_amount),
+    );
+
+    Ok(())
+}
+
+fn attestation_deadline(trade: &Trade) -> u64 {
+    trade.delivery_slot + SLOT_SECONDS + ATTESTATION_WINDOW_SECONDS
+}
 
-    // Require buyer authorization
-    trade.buyer.require_auth();
+/// Split the escrowed payment and bond between seller and buyer by delivered quantity
+fn execute_payment(env: &Env, trade: &Trade) -> Result<(), MarketplaceError> {
+    let shortfall = trade.quantity_kwh - trade.delivered_kwh;
+    let payment = trade.total_amount * trade.delivered_kwh / trade.quantity_kwh;
+    let penalty = trade.bond_amount * shortfall / trade.quantity_kwh;
 
-    // Execute transfer from buyer to seller
-    let token_client = token::Client::new(env, &token_contract);
-    token_client.transfer(&trade.buyer, &trade.seller, &(trade.total_amount as i128));
+    escrow::release(env, &trade.seller, payment + trade.bond_amount - penalty)?;
+    escrow::release(env, &trade.buyer, trade.total_amount - payment + penalty)?;
 
     // Emit payment event
     env.events().publish(
         (symbol_short!("payment"), trade.buyer.clone()),
-        (trade.seller.clone(), trade.total_amount),
+        (trade.seller.clone(), payment),
     );
+    // Certificates follow the delivered energy; MWh not delivered go back to the seller
+    if let Some(rec_id) = trade.origin.rec_id() {
+        let certified_kwh = trade.delivered_kwh - trade.delivered_kwh % KWH_PER_MWH;
+        certificates::release(env, rec_id, &trade.buyer, certified_kwh)?;
+        certificates::release(
+            env,
+            rec_id,
+            &trade.seller,
+            trade.quantity_kwh - certified_kwh,
+        )?;
+    }
+
+    if shortfall > 0 {
+        env.events().publish(
+            (symbol_short!("penalty"), trade.seller.clone()),
+            (trade.trade_id, shortfall, penalty),
+        );
+    }
 
     Ok(())
 }
 
 /// Get trade details
 pub fn get_trade(env: &Env, trade_id: u64) -> Result<Trade, MarketplaceError> {
-    let trades: Map<u64, Trade> = env
-        .storage()
-        .instance()
-        .get(&DataKey::Trades)
-        .unwrap_or_else(|| Map::new(env));
-
-    trades.get(trade_id).ok_or(MarketplaceError::TradeNotFound)
+    storage::load_trade(env, trade_id)
 }
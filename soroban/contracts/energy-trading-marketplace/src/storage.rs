@@ -0,0 +1,172 @@
+use crate::utils::*;
+use soroban_sdk::{Env, IntoVal, TryFromVal, Val, Vec};
+
+/// Load an order from its persistent entry
+pub fn load_order(env: &Env, order_id: u64) -> Result<EnergyOrder, MarketplaceError> {
+    get_persistent(env, &MarketKey::Order(order_id)).ok_or(MarketplaceError::OrderNotFound)
+}
+
+/// Store an order, indexing it by trader when new and moving it between status indexes
+pub fn save_order(env: &Env, order: &EnergyOrder) {
+    let key = MarketKey::Order(order.order_id);
+    let previous: Option<EnergyOrder> = env.storage().persistent().get(&key);
+    match previous {
+        None => {
+            index_push(
+                env,
+                &Index::TraderOrders(order.trader.clone()),
+                order.order_id,
+            );
+            index_push(
+                env,
+                &Index::OrderStatus(order.status.clone()),
+                order.order_id,
+            );
+        }
+        Some(previous) if previous.status != order.status => {
+            index_remove(env, &Index::OrderStatus(previous.status), order.order_id);
+            index_push(
+                env,
+                &Index::OrderStatus(order.status.clone()),
+                order.order_id,
+            );
+        }
+        Some(_) => {}
+    }
+    set_persistent(env, &key, order);
+}
+
+/// Load a trade from its persistent entry
+pub fn load_trade(env: &Env, trade_id: u64) -> Result<Trade, MarketplaceError> {
+    get_persistent(env, &MarketKey::Trade(trade_id)).ok_or(MarketplaceError::TradeNotFound)
+}
+
+/// Store a trade, indexing it under both traders when new and moving it between status indexes
+pub fn save_trade(env: &Env, trade: &Trade) {
+    let key = MarketKey::Trade(trade.trade_id);
+    let previous: Option<Trade> = env.storage().persistent().get(&key);
+    match previous {
+        None => {
+            index_push(
+                env,
+                &Index::TraderTrades(trade.buyer.clone()),
+                trade.trade_id,
+            );
+            index_push(
+                env,
+                &Index::TraderTrades(trade.seller.clone()),
+                trade.trade_id,
+            );
+            index_push(
+                env,
+                &Index::TradeStatus(trade.status.clone()),
+                trade.trade_id,
+            );
+        }
+        Some(previous) if previous.status != trade.status => {
+            index_remove(env, &Index::TradeStatus(previous.status), trade.trade_id);
+            index_push(
+                env,
+                &Index::TradeStatus(trade.status.clone()),
+                trade.trade_id,
+            );
+        }
+        Some(_) => {}
+    }
+    set_persistent(env, &key, trade);
+}
+
+/// Page through the orders in an index
+pub fn list_orders(env: &Env, index: &Index, offset: u32, limit: u32) -> Vec<EnergyOrder> {
+    let mut orders = Vec::new(env);
+    for order_id in list_ids(env, index, offset, limit).iter() {
+        orders.push_back(load_order(env, order_id).unwrap());
+    }
+    orders
+}
+
+/// Page through the trades in an index
+pub fn list_trades(env: &Env, index: &Index, offset: u32, limit: u32) -> Vec<Trade> {
+    let mut trades = Vec::new(env);
+    for trade_id in list_ids(env, index, offset, limit).iter() {
+        trades.push_back(load_trade(env, trade_id).unwrap());
+    }
+    trades
+}
+
+/// Number of entries in an index
+pub fn index_len(env: &Env, index: &Index) -> u32 {
+    get_persistent(env, &MarketKey::IndexLength(index.clone())).unwrap_or(0)
+}
+
+/// Trader indexes are in creation order; status indexes are unordered since removals
+/// move the last entry into the freed position
+fn list_ids(env: &Env, index: &Index, offset: u32, limit: u32) -> Vec<u64> {
+    let end = offset.saturating_add(limit).min(index_len(env, index));
+    let mut ids = Vec::new(env);
+    for position in offset..end {
+        ids.push_back(
+            get_persistent(env, &MarketKey::IndexEntry(index.clone(), position)).unwrap(),
+        );
+    }
+    ids
+}
+
+fn index_push(env: &Env, index: &Index, id: u64) {
+    let position = index_len(env, index);
+    set_persistent(env, &MarketKey::IndexEntry(index.clone(), position), &id);
+    set_persistent(env, &MarketKey::IndexPosition(index.clone(), id), &position);
+    set_persistent(env, &MarketKey::IndexLength(index.clone()), &(position + 1));
+}
+
+/// Remove an ID in constant time by moving the last entry into its position, so only
+/// indexes that never shrink keep their insertion order
+fn index_remove(env: &Env, index: &Index, id: u64) {
+    let position_key = MarketKey::IndexPosition(index.clone(), id);
+    let Some(position) = get_persistent::<u32>(env, &position_key) else {
+        return;
+    };
+    let last = index_len(env, index) - 1;
+    let last_key = MarketKey::IndexEntry(index.clone(), last);
+
+    // Move the last entry into the freed position
+    if position != last {
+        let last_id: u64 = get_persistent(env, &last_key).unwrap();
+        set_persistent(
+            env,
+            &MarketKey::IndexEntry(index.clone(), position),
+            &last_id,
+        );
+        set_persistent(
+            env,
+            &MarketKey::IndexPosition(index.clone(), last_id),
+            &position,
+        );
+    }
+    env.storage().persistent().remove(&last_key);
+    env.storage().persistent().remove(&position_key);
+    set_persistent(env, &MarketKey::IndexLength(index.clone()), &last);
+}
+
+/// Read a persistent entry, extending its TTL when present
+pub fn get_persistent<V: TryFromVal<Env, Val>>(env: &Env, key: &MarketKey) -> Option<V> {
+    let value = env.storage().persistent().get(key);
+    if value.is_some() {
+        env.storage().persistent().extend_ttl(
+            key,
+            PERSISTENT_LIFETIME_THRESHOLD,
+            PERSISTENT_BUMP_AMOUNT,
+        );
+    }
+    value
+}
+
+/// Write a persistent entry and extend its TTL
+pub fn set_persistent<V: IntoVal<Env, Val>>(env: &Env, key: &MarketKey, value: &V) {
+    env.storage().persistent().set(key, value);
+    env.storage().persistent().extend_ttl(
+        key,
+        PERSISTENT_LIFETIME_THRESHOLD,
+        PERSISTENT_BUMP_AMOUNT,
+    );
+}
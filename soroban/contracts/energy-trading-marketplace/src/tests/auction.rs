@@ -0,0 +1,279 @@
+#![cfg(test)]
+
+use crate::tests::utils::{mint_tokens, setup_test_environment, token_balance, STARTING_BALANCE};
+use crate::utils::{
+    AuctionStatus, MarketKey, MarketplaceError, OrderStatus, OrderType, TimeInForce, TradeStatus,
+};
+use crate::EnergyTradingMarketplaceClient;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, BytesN, Env};
+
+const AUCTION_SLOT: u64 = 4 * 3_600;
+const BIDDING_CLOSES_AT: u64 = 1_000;
+const REVEAL_CLOSES_AT: u64 = 2_000;
+
+fn seal(
+    env: &Env,
+    client: &EnergyTradingMarketplaceClient,
+    trader: &Address,
+    order_type: OrderType,
+    quantity: u64,
+    price: u64,
+    deposit: u64,
+) -> (u64, BytesN<32>) {
+    let salt = BytesN::from_array(env, &[quantity as u8; 32]);
+    let commitment =
+        client.compute_commitment(trader, &AUCTION_SLOT, &order_type, &quantity, &price, &salt);
+    let bid_id = client.submit_sealed_order(trader, &AUCTION_SLOT, &commitment, &deposit);
+    (bid_id, salt)
+}
+
+#[test]
+fn test_open_auction_requires_grid_operator() {
+    let (env, client, _admin, _token, producer, _consumer) = setup_test_environment();
+
+    let result = client.try_open_auction(
+        &producer,
+        &AUCTION_SLOT,
+        &BIDDING_CLOSES_AT,
+        &REVEAL_CLOSES_AT,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+
+    // Reveals must close before delivery starts
+    let operator = Address::generate(&env);
+    client.register_grid_operator(&operator);
+    let result = client.try_open_auction(
+        &operator,
+        &AUCTION_SLOT,
+        &BIDDING_CLOSES_AT,
+        &(AUCTION_SLOT + 1),
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidInput)));
+
+    client.open_auction(
+        &operator,
+        &AUCTION_SLOT,
+        &BIDDING_CLOSES_AT,
+        &REVEAL_CLOSES_AT,
+    );
+    let auction = client.get_auction(&AUCTION_SLOT);
+    assert_eq!(auction.operator, operator);
+    assert_eq!(auction.status, AuctionStatus::Open);
+}
+
+#[test]
+fn test_sealed_bid_auction_clears_at_uniform_price() {
+    let (env, client, admin, token, producer, consumer) = setup_test_environment();
+
+    let producer2 = Address::generate(&env);
+    let consumer2 = Address::generate(&env);
+    client.register_producer(&producer2);
+    client.register_consumer(&consumer2);
+    mint_tokens(&env, &token, &admin, &producer2, STARTING_BALANCE);
+    mint_tokens(&env, &token, &admin, &consumer2, STARTING_BALANCE);
+
+    let operator = Address::generate(&env);
+    client.register_grid_operator(&operator);
+    client.open_auction(
+        &operator,
+        &AUCTION_SLOT,
+        &BIDDING_CLOSES_AT,
+        &REVEAL_CLOSES_AT,
+    );
+
+    // Continuous orders are closed for a slot under auction
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &40u64,
+        &AUCTION_SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::SlotInAuction)));
+
+    // Bidding phase: only commitments and deposits are visible
+    let (cheap_bid, cheap_salt) = seal(&env, &client, &producer, OrderType::Sell, 100, 40, 1_000);
+    let (dear_bid, dear_salt) = seal(&env, &client, &producer2, OrderType::Sell, 100, 55, 1_000);
+    let (big_bid, big_salt) = seal(&env, &client, &consumer, OrderType::Buy, 150, 60, 10_000);
+    let (low_bid, low_salt) = seal(&env, &client, &consumer2, OrderType::Buy, 100, 50, 5_000);
+    let (unrevealed, _) = seal(&env, &client, &producer, OrderType::Sell, 50, 30, 500);
+    assert_eq!(
+        token_balance(&env, &token, &consumer),
+        STARTING_BALANCE - 10_000
+    );
+    assert!(unrevealed > low_bid);
+
+    // Reveals are rejected until bidding closes, and new bids after it
+    let result = client.try_reveal_sealed_order(
+        &consumer,
+        &big_bid,
+        &OrderType::Buy,
+        &150u64,
+        &60u64,
+        &big_salt,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::AuctionPhaseError)));
+    env.ledger().with_mut(|li| li.timestamp = BIDDING_CLOSES_AT);
+    let commitment = client.compute_commitment(
+        &consumer,
+        &AUCTION_SLOT,
+        &OrderType::Buy,
+        &10u64,
+        &60u64,
+        &big_salt,
+    );
+    let result = client.try_submit_sealed_order(&consumer, &AUCTION_SLOT, &commitment, &600u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::AuctionPhaseError)));
+
+    // A reveal must match the commitment exactly
+    let result = client.try_reveal_sealed_order(
+        &consumer,
+        &big_bid,
+        &OrderType::Buy,
+        &150u64,
+        &59u64,
+        &big_salt,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::CommitmentMismatch)));
+
+    let big_order = client.reveal_sealed_order(
+        &consumer,
+        &big_bid,
+        &OrderType::Buy,
+        &150u64,
+        &60u64,
+        &big_salt,
+    );
+    let low_order = client.reveal_sealed_order(
+        &consumer2,
+        &low_bid,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &low_salt,
+    );
+    let cheap_order = client.reveal_sealed_order(
+        &producer,
+        &cheap_bid,
+        &OrderType::Sell,
+        &100u64,
+        &40u64,
+        &cheap_salt,
+    );
+    let dear_order = client.reveal_sealed_order(
+        &producer2,
+        &dear_bid,
+        &OrderType::Sell,
+        &100u64,
+        &55u64,
+        &dear_salt,
+    );
+
+    // Deposits beyond the revealed order's collateral come straight back
+    assert_eq!(
+        token_balance(&env, &token, &consumer),
+        STARTING_BALANCE - 9_000
+    );
+
+    // Nothing clears before the reveal phase ends
+    let result = client.try_clear_auction(&AUCTION_SLOT);
+    assert_eq!(result, Err(Ok(MarketplaceError::AuctionPhaseError)));
+
+    env.ledger().with_mut(|li| li.timestamp = REVEAL_CLOSES_AT);
+    let auction = client.clear_auction(&AUCTION_SLOT);
+    assert_eq!(auction.status, AuctionStatus::Cleared);
+    assert_eq!(auction.cleared_quantity, 150u64);
+    // The marginal accepted offer sets the price for every match
+    assert_eq!(auction.clearing_price, 55u64);
+
+    let trades = client.get_trade_history(&consumer, &0u32, &10u32);
+    assert_eq!(trades.len(), 2);
+    for trade in trades.iter() {
+        assert_eq!(trade.price_per_kwh, 55u64);
+        assert_eq!(trade.delivery_slot, AUCTION_SLOT);
+        assert_eq!(trade.status, TradeStatus::Pending);
+    }
+
+    assert_eq!(client.get_order(&big_order).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&cheap_order).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&low_order).status, OrderStatus::Cancelled);
+    let dear = client.get_order(&dear_order);
+    assert_eq!(dear.status, OrderStatus::Cancelled);
+    assert_eq!(dear.filled_quantity, 50u64);
+
+    // Buyers pay the clearing price, unmatched collateral and unrevealed deposits are returned
+    assert_eq!(
+        token_balance(&env, &token, &consumer),
+        STARTING_BALANCE - 150 * 55
+    );
+    assert_eq!(token_balance(&env, &token, &consumer2), STARTING_BALANCE);
+    assert_eq!(
+        token_balance(&env, &token, &producer),
+        STARTING_BALANCE - 400
+    );
+    assert_eq!(
+        token_balance(&env, &token, &producer2),
+        STARTING_BALANCE - 275
+    );
+
+    // Sealed bids are removed once the auction clears
+    assert!(auction.sealed_bids.is_empty());
+    env.as_contract(&client.address, || {
+        for bid_id in [cheap_bid, dear_bid, big_bid, low_bid, unrevealed] {
+            assert!(!env
+                .storage()
+                .persistent()
+                .has(&MarketKey::SealedBid(bid_id)));
+        }
+    });
+
+    let result = client.try_clear_auction(&AUCTION_SLOT);
+    assert_eq!(result, Err(Ok(MarketplaceError::AuctionPhaseError)));
+}
+
+#[test]
+fn test_copied_commitment_cannot_be_revealed_by_another_trader() {
+    let (env, client, admin, token, _producer, consumer) = setup_test_environment();
+
+    let copycat = Address::generate(&env);
+    client.register_consumer(&copycat);
+    mint_tokens(&env, &token, &admin, &copycat, STARTING_BALANCE);
+
+    let operator = Address::generate(&env);
+    client.register_grid_operator(&operator);
+    client.open_auction(
+        &operator,
+        &AUCTION_SLOT,
+        &BIDDING_CLOSES_AT,
+        &REVEAL_CLOSES_AT,
+    );
+
+    // The copycat submits the consumer's commitment and learns the order once it is revealed
+    let salt = BytesN::from_array(&env, &[7u8; 32]);
+    let commitment = client.compute_commitment(
+        &consumer,
+        &AUCTION_SLOT,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &salt,
+    );
+    let copied_bid = client.submit_sealed_order(&copycat, &AUCTION_SLOT, &commitment, &6_000u64);
+    let bid_id = client.submit_sealed_order(&consumer, &AUCTION_SLOT, &commitment, &6_000u64);
+
+    env.ledger().with_mut(|li| li.timestamp = BIDDING_CLOSES_AT);
+    client.reveal_sealed_order(&consumer, &bid_id, &OrderType::Buy, &100u64, &60u64, &salt);
+
+    // The commitment is bound to the consumer, so the copy cannot be opened
+    let result = client.try_reveal_sealed_order(
+        &copycat,
+        &copied_bid,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &salt,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::CommitmentMismatch)));
+}
@@ -0,0 +1,228 @@
+#![cfg(test)]
+
+use crate::tests::utils::{
+    mint_tokens, setup_test_environment, token_balance, SLOT, STARTING_BALANCE,
+};
+use crate::utils::{EnergyOrigin, MarketplaceError, OrderStatus, OrderType, TimeInForce};
+use renewable_energy_certificate_tracker::{
+    EnergySource, RenewableEnergyCertificateTracker, RenewableEnergyCertificateTrackerClient,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{Address, BytesN, Env, Map, String};
+
+/// Register the certificate tracker with `issuer` allowed to issue certificates
+fn register_rec_tracker(
+    env: &Env,
+    issuer: &Address,
+) -> RenewableEnergyCertificateTrackerClient<'static> {
+    let tracker = RenewableEnergyCertificateTrackerClient::new(
+        env,
+        &env.register(RenewableEnergyCertificateTracker, ()),
+    );
+    tracker.initialize(&Address::generate(env));
+    tracker.register_issuer(issuer, &String::from_str(env, "Test Farm"));
+    tracker
+}
+
+/// Issue a certificate the issuer itself owns
+fn issue_rec(
+    env: &Env,
+    tracker: &RenewableEnergyCertificateTrackerClient,
+    issuer: &Address,
+    capacity_mwh: i128,
+) -> BytesN<32> {
+    tracker.issue_rec(
+        issuer,
+        &EnergySource::Solar,
+        &0u64,
+        &String::from_str(env, "Test Farm"),
+        &capacity_mwh,
+        &String::from_str(env, "I-REC"),
+        &BytesN::from_array(env, &[capacity_mwh as u8; 32]),
+        &Map::new(env),
+    )
+}
+
+#[test]
+fn test_certified_sale_transfers_certificate_at_settlement() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+    let tracker = register_rec_tracker(&env, &producer);
+    client.set_rec_contract(&tracker.address);
+    let rec_id = issue_rec(&env, &tracker, &producer, 5);
+
+    // The certificate's MWh for the order move into marketplace custody
+    let sell_order_id = client.place_certified_sell_order(
+        &producer,
+        &2_000u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &5u64,
+    );
+    // The tracker splits the 2 MWh off into a certificate the order now references
+    let custody_id = match client.get_order(&sell_order_id).origin {
+        EnergyOrigin::Certified(custody_id) => custody_id,
+        EnergyOrigin::Unspecified => panic!("order lost its certificate"),
+    };
+    assert_ne!(custody_id, rec_id);
+    let custody = tracker.get_rec_status(&custody_id);
+    assert_eq!(custody.capacity_mwh, 2);
+    assert_eq!(custody.current_owner, client.address);
+    let rec = tracker.get_rec_status(&rec_id);
+    assert_eq!(rec.capacity_mwh, 3);
+    assert_eq!(rec.current_owner, producer);
+
+    // The buyer locks price plus maximum premium and pays the asked premium
+    client.place_green_buy_order(
+        &consumer,
+        &2_000u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &10u64,
+    );
+    let trade = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap();
+    assert_eq!(trade.green_premium, 5u64);
+    assert_eq!(trade.total_amount, 2_000 * 55);
+    assert_eq!(
+        token_balance(&env, &token, &consumer),
+        STARTING_BALANCE - 110_000
+    );
+
+    // Only whole delivered MWh earn certificates; the rest return to the seller
+    let operator = Address::generate(&env);
+    client.register_grid_operator(&operator);
+    env.ledger().with_mut(|li| li.timestamp = SLOT + 3_600);
+    client.attest_delivery(&operator, &trade.trade_id, &1_500u64);
+    client.settle_trade(&trade.trade_id, &consumer);
+
+    // The buyer's MWh are split off the custody certificate, whose remainder goes back
+    let delivered = tracker.get_child_recs(&custody_id);
+    assert_eq!(delivered.len(), 1);
+    let delivered = tracker.get_rec_status(&delivered.get(0).unwrap());
+    assert_eq!(delivered.capacity_mwh, 1);
+    assert_eq!(delivered.current_owner, consumer);
+    let custody = tracker.get_rec_status(&custody_id);
+    assert_eq!(custody.capacity_mwh, 1);
+    assert_eq!(custody.current_owner, producer);
+}
+
+#[test]
+fn test_green_buy_only_matches_certified_supply_within_premium() {
+    let (env, client, admin, token, producer, consumer) = setup_test_environment();
+    let producer2 = Address::generate(&env);
+    client.register_producer(&producer2);
+    mint_tokens(&env, &token, &admin, &producer2, STARTING_BALANCE);
+    let tracker = register_rec_tracker(&env, &producer2);
+    client.set_rec_contract(&tracker.address);
+    let rec_id = issue_rec(&env, &tracker, &producer2, 10);
+
+    let generic = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &1_000u64,
+        &40u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let pricey = client.place_certified_sell_order(
+        &producer2,
+        &1_000u64,
+        &45u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &20u64,
+    );
+
+    // The cheaper generic supply and the certificate above the premium cap are both skipped
+    let buy_order_id = client.place_green_buy_order(
+        &consumer,
+        &1_000u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &10u64,
+    );
+    assert_eq!(client.get_order(&buy_order_id).filled_quantity, 0u64);
+    assert_eq!(client.get_order(&generic).status, OrderStatus::Active);
+    assert_eq!(client.get_order(&pricey).status, OrderStatus::Active);
+
+    // A plain buy never pays a green premium either
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &1_000u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(client.get_order(&generic).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&pricey).status, OrderStatus::Active);
+
+    // Cancelling a certified sell returns its certificate
+    let custody_id = match client.get_order(&pricey).origin {
+        EnergyOrigin::Certified(custody_id) => custody_id,
+        EnergyOrigin::Unspecified => panic!("order lost its certificate"),
+    };
+    assert_eq!(tracker.get_rec_status(&rec_id).capacity_mwh, 9);
+    client.cancel_order(&producer2, &pricey);
+    assert_eq!(tracker.get_rec_status(&custody_id).current_owner, producer2);
+    assert_eq!(tracker.get_rec_status(&custody_id).capacity_mwh, 1);
+}
+
+#[test]
+fn test_certified_sell_order_validation() {
+    let (env, client, _admin, _token, producer, consumer) = setup_test_environment();
+    let rec_id = BytesN::from_array(&env, &[9u8; 32]);
+    let result = client.try_place_certified_sell_order(
+        &producer,
+        &1_000u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &0u64,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::RecContractNotSet)));
+
+    let tracker = register_rec_tracker(&env, &producer);
+    client.set_rec_contract(&tracker.address);
+    let rec_id = issue_rec(&env, &tracker, &producer, 1);
+
+    // Certificates back whole MWh owned by the seller
+    let result = client.try_place_certified_sell_order(
+        &producer,
+        &1_500u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &0u64,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidInput)));
+    let result = client.try_place_certified_sell_order(
+        &producer,
+        &2_000u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &0u64,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::CertificateInvalid)));
+    let result = client.try_place_certified_sell_order(
+        &consumer,
+        &1_000u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+        &rec_id,
+        &0u64,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::CertificateInvalid)));
+}
@@ -1,7 +1,9 @@
 #![cfg(test)]
 
-use crate::tests::utils::setup_test_environment;
-use crate::utils::{OrderStatus, OrderType};
+use crate::tests::utils::{
+    mint_tokens, setup_test_environment, token_balance, SLOT, STARTING_BALANCE,
+};
+use crate::utils::{OrderStatus, OrderType, TimeInForce};
 use soroban_sdk::testutils::Address as _;
 extern crate alloc;
 use alloc::vec::Vec;
@@ -11,10 +13,24 @@ fn test_automatic_order_matching() {
     let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
 
     // Place sell order first
-    let sell_order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Place matching buy order - should trigger match
-    let buy_order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Check orders are filled
     let sell_order = client.get_order(&sell_order_id);
@@ -24,7 +40,7 @@ fn test_automatic_order_matching() {
     assert_eq!(buy_order.status, OrderStatus::Filled);
 
     // Verify trade was created
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 1);
 
     let trade = &trade_history.get(0).unwrap();
@@ -39,10 +55,24 @@ fn test_no_matching_incompatible_prices() {
     let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
 
     // Place sell order with high price
-    let sell_order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &100u64);
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &100u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Place buy order with lower price - should not match
-    let buy_order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &50u64);
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Check orders remain active
     let sell_order = client.get_order(&sell_order_id);
@@ -52,7 +82,7 @@ fn test_no_matching_incompatible_prices() {
     assert_eq!(buy_order.status, OrderStatus::Active);
 
     // Verify no trade was created
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 0);
 }
 
@@ -61,8 +91,22 @@ fn test_matching_with_exact_price() {
     let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
 
     // Place orders with exact same price
-    let sell_order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    let buy_order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &50u64);
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Check orders are filled
     let sell_order = client.get_order(&sell_order_id);
@@ -77,20 +121,35 @@ fn test_matching_different_quantities() {
     let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
 
     // Place sell order with larger quantity
-    let sell_order_id = client.place_order(&producer, &OrderType::Sell, &200u64, &50u64);
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &200u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Place buy order with smaller quantity
-    let buy_order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
-    // Check buy order is filled, sell order remains active
+    // Check buy order is filled, sell order remains active with the remainder
     let sell_order = client.get_order(&sell_order_id);
     let buy_order = client.get_order(&buy_order_id);
 
     assert_eq!(buy_order.status, OrderStatus::Filled);
-    assert_eq!(sell_order.status, OrderStatus::Filled); // Current implementation fills both
+    assert_eq!(sell_order.status, OrderStatus::Active);
+    assert_eq!(sell_order.filled_quantity, 100u64);
 
     // Verify trade quantity matches smaller order
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 1);
 
     let trade = &trade_history.get(0).unwrap();
@@ -99,21 +158,44 @@ fn test_matching_different_quantities() {
 
 #[test]
 fn test_multiple_order_matching() {
-    let (env, client, _admin, _token, producer, consumer) = setup_test_environment();
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
 
     // Create additional traders
     let producer2 = soroban_sdk::Address::generate(&env);
     let consumer2 = soroban_sdk::Address::generate(&env);
 
     client.register_producer(&producer2);
+    mint_tokens(&env, &token, &_admin, &producer2, STARTING_BALANCE);
     client.register_consumer(&consumer2);
+    mint_tokens(&env, &token, &_admin, &consumer2, STARTING_BALANCE);
 
     // Place multiple sell orders
-    let sell_order_id1 = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    let sell_order_id2 = client.place_order(&producer2, &OrderType::Sell, &100u64, &55u64);
+    let sell_order_id1 = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let sell_order_id2 = client.place_order(
+        &producer2,
+        &OrderType::Sell,
+        &100u64,
+        &55u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Place buy order that can match the first (cheaper) sell order
-    let buy_order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Check first sell order is matched
     let sell_order1 = client.get_order(&sell_order_id1);
@@ -130,8 +212,22 @@ fn test_same_trader_no_self_match() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
     // Producer tries to both buy and sell
-    let sell_order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    let buy_order_id = client.place_order(&producer, &OrderType::Buy, &100u64, &60u64);
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let buy_order_id = client.place_order(
+        &producer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Orders should not match with themselves
     let sell_order = client.get_order(&sell_order_id);
@@ -141,13 +237,13 @@ fn test_same_trader_no_self_match() {
     assert_eq!(buy_order.status, OrderStatus::Active);
 
     // No trade should be created
-    let trade_history = client.get_trade_history(&producer);
+    let trade_history = client.get_trade_history(&producer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 0);
 }
 
 #[test]
 fn test_high_volume_order_placement() {
-    let (env, client, _admin, _token, _producer, _consumer) = setup_test_environment();
+    let (env, client, _admin, token, _producer, _consumer) = setup_test_environment();
 
     // Create multiple traders for scalability testing
     let mut producers = Vec::new();
@@ -159,7 +255,9 @@ fn test_high_volume_order_placement() {
         let consumer = soroban_sdk::Address::generate(&env);
 
         client.register_producer(&producer);
+        mint_tokens(&env, &token, &_admin, &producer, STARTING_BALANCE);
         client.register_consumer(&consumer);
+        mint_tokens(&env, &token, &_admin, &consumer, STARTING_BALANCE);
 
         producers.push(producer);
         consumers.push(consumer);
@@ -172,7 +270,14 @@ fn test_high_volume_order_placement() {
         let price = 50 + (i as u64 % 10); // Vary prices 50-59
         let quantity = 100 + (i as u64 * 10); // Vary quantities
 
-        let order_id = client.place_order(producer, &OrderType::Sell, &quantity, &price);
+        let order_id = client.place_order(
+            producer,
+            &OrderType::Sell,
+            &quantity,
+            &price,
+            &SLOT,
+            &TimeInForce::GoodTillCancelled,
+        );
         sell_order_ids.push(order_id);
     }
 
@@ -183,14 +288,21 @@ fn test_high_volume_order_placement() {
         let price = 55 + (i as u64 % 5); // Prices 55-59, higher than some sells
         let quantity = 150 + (i as u64 * 5); // Vary quantities
 
-        let order_id = client.place_order(consumer, &OrderType::Buy, &quantity, &price);
+        let order_id = client.place_order(
+            consumer,
+            &OrderType::Buy,
+            &quantity,
+            &price,
+            &SLOT,
+            &TimeInForce::GoodTillCancelled,
+        );
         buy_order_ids.push(order_id);
     }
 
     // Verify that some orders were matched (trades created)
     let mut total_trades = 0;
     for consumer in &consumers {
-        let trades = client.get_trade_history(consumer);
+        let trades = client.get_trade_history(consumer, &0u32, &10u32);
         total_trades += trades.len();
     }
 
@@ -211,3 +323,310 @@ fn test_high_volume_order_placement() {
         assert_eq!(order_id, (50 + i + 1) as u64);
     }
 }
+
+#[test]
+fn test_price_time_priority() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let producer2 = soroban_sdk::Address::generate(&env);
+    client.register_producer(&producer2);
+    mint_tokens(&env, &token, &_admin, &producer2, STARTING_BALANCE);
+
+    // Two sells at the same price and a cheaper one placed last
+    let early = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &55u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let late = client.place_order(
+        &producer2,
+        &OrderType::Sell,
+        &100u64,
+        &55u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let cheapest = client.place_order(
+        &producer2,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    let book = client.get_order_book(&OrderType::Sell, &SLOT, &10u32);
+    let ids: Vec<u64> = book.iter().map(|order| order.order_id).collect();
+    assert_eq!(ids, [cheapest, early, late]);
+
+    // The best price fills first, then the earlier order at the next price
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &150u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    assert_eq!(client.get_order(&cheapest).status, OrderStatus::Filled);
+    let early_order = client.get_order(&early);
+    assert_eq!(early_order.status, OrderStatus::Active);
+    assert_eq!(early_order.filled_quantity, 50u64);
+    assert_eq!(client.get_order(&late).filled_quantity, 0u64);
+
+    // Each fill trades at the resting order's price
+    let trades = client.get_trade_history(&consumer, &0u32, &10u32);
+    assert_eq!(trades.len(), 2);
+    assert_eq!(trades.get(0).unwrap().price_per_kwh, 50u64);
+    assert_eq!(trades.get(0).unwrap().sell_order_id, cheapest);
+    assert_eq!(trades.get(1).unwrap().price_per_kwh, 55u64);
+    assert_eq!(trades.get(1).unwrap().quantity_kwh, 50u64);
+}
+
+#[test]
+fn test_matching_stops_at_first_price_that_does_not_cross() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let producer2 = soroban_sdk::Address::generate(&env);
+    client.register_producer(&producer2);
+    mint_tokens(&env, &token, &_admin, &producer2, STARTING_BALANCE);
+
+    // Placed out of price order; the book keeps them best price first
+    let mut sells = [0u64; 4];
+    for (index, (seller, price)) in [
+        (&producer, 70u64),
+        (&producer2, 55u64),
+        (&producer, 60u64),
+        (&producer2, 50u64),
+    ]
+    .iter()
+    .enumerate()
+    {
+        sells[index] = client.place_order(
+            seller,
+            &OrderType::Sell,
+            &100u64,
+            price,
+            &SLOT,
+            &TimeInForce::GoodTillCancelled,
+        );
+    }
+    let [at_70, at_55, at_60, at_50] = sells;
+    let book = client.get_order_book(&OrderType::Sell, &SLOT, &10u32);
+    let ids: Vec<u64> = book.iter().map(|order| order.order_id).collect();
+    assert_eq!(ids, [at_50, at_55, at_60, at_70]);
+
+    // The buy sweeps the crossing prices and rests the rest without touching dearer asks
+    let buy = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &300u64,
+        &58u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    assert_eq!(client.get_order(&at_50).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&at_55).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&at_60).filled_quantity, 0u64);
+    let book = client.get_order_book(&OrderType::Sell, &SLOT, &10u32);
+    let ids: Vec<u64> = book.iter().map(|order| order.order_id).collect();
+    assert_eq!(ids, [at_60, at_70]);
+
+    let bids = client.get_order_book(&OrderType::Buy, &SLOT, &10u32);
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids.get(0).unwrap().order_id, buy);
+    assert_eq!(bids.get(0).unwrap().filled_quantity, 200u64);
+}
+
+#[test]
+fn test_partial_fill_sweeps_book_and_rests_remainder() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let consumer2 = soroban_sdk::Address::generate(&env);
+    client.register_consumer(&consumer2);
+    mint_tokens(&env, &token, &_admin, &consumer2, STARTING_BALANCE);
+
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer2,
+        &OrderType::Buy,
+        &100u64,
+        &58u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let too_low = client.place_order(
+        &consumer2,
+        &OrderType::Buy,
+        &100u64,
+        &40u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // The sell crosses both bids above its limit and rests the rest at 50
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &300u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    let sell_order = client.get_order(&sell_order_id);
+    assert_eq!(sell_order.status, OrderStatus::Active);
+    assert_eq!(sell_order.filled_quantity, 200u64);
+    assert_eq!(client.get_trade_history(&producer, &0u32, &10u32).len(), 2);
+
+    let bids = client.get_order_book(&OrderType::Buy, &SLOT, &10u32);
+    assert_eq!(bids.len(), 1);
+    assert_eq!(bids.get(0).unwrap().order_id, too_low);
+    let asks = client.get_order_book(&OrderType::Sell, &SLOT, &10u32);
+    assert_eq!(asks.len(), 1);
+    assert_eq!(asks.get(0).unwrap().order_id, sell_order_id);
+
+    // Cancelling takes the remainder off the book
+    client.cancel_order(&producer, &sell_order_id);
+    assert_eq!(
+        client.get_order_book(&OrderType::Sell, &SLOT, &10u32).len(),
+        0
+    );
+}
+
+#[test]
+fn test_orders_only_match_within_their_delivery_slot() {
+    let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
+
+    let later_slot = SLOT + 3_600;
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &later_slot,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // Crossing prices in different slots leave both orders resting
+    assert_eq!(client.get_order(&sell_order_id).status, OrderStatus::Active);
+    assert_eq!(client.get_order(&buy_order_id).status, OrderStatus::Active);
+    assert_eq!(
+        client.get_order_book(&OrderType::Buy, &SLOT, &10u32).len(),
+        0
+    );
+    assert_eq!(
+        client
+            .get_order_book(&OrderType::Buy, &later_slot, &10u32)
+            .len(),
+        1
+    );
+
+    // A buy in the same slot matches and the trade carries the slot
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(client.get_order(&sell_order_id).status, OrderStatus::Filled);
+    let trade = client.get_trade(&1u64);
+    assert_eq!(trade.delivery_slot, SLOT);
+}
+
+#[test]
+fn test_immediate_or_cancel_never_rests() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // Fills what crosses now and cancels the remainder, refunding its escrow
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &150u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::ImmediateOrCancel,
+    );
+    let buy_order = client.get_order(&buy_order_id);
+    assert_eq!(buy_order.status, OrderStatus::Cancelled);
+    assert_eq!(buy_order.filled_quantity, 100u64);
+    assert_eq!(buy_order.escrow_amount, 0u64);
+    assert_eq!(
+        client.get_order_book(&OrderType::Buy, &SLOT, &10u32).len(),
+        0
+    );
+    assert_eq!(
+        token_balance(&env, &token, &consumer),
+        STARTING_BALANCE - 5_000
+    );
+}
+
+#[test]
+fn test_fill_or_kill_fills_completely_or_not_at_all() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // Not enough liquidity at the limit price: nothing trades and the escrow comes back
+    let killed = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &150u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::FillOrKill,
+    );
+    assert_eq!(client.get_order(&killed).status, OrderStatus::Cancelled);
+    assert_eq!(client.get_order(&killed).filled_quantity, 0u64);
+    assert_eq!(client.get_order(&sell_order_id).filled_quantity, 0u64);
+    assert_eq!(token_balance(&env, &token, &consumer), STARTING_BALANCE);
+
+    let filled = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::FillOrKill,
+    );
+    assert_eq!(client.get_order(&filled).status, OrderStatus::Filled);
+    assert_eq!(client.get_order(&sell_order_id).status, OrderStatus::Filled);
+}
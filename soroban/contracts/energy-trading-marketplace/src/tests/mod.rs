@@ -1,4 +1,7 @@
+mod auction;
+mod green;
 mod matching;
 mod settlement;
+mod storage;
 mod trading;
 mod utils;
@@ -1,8 +1,13 @@
 #![cfg(test)]
 
-use crate::tests::utils::{mint_tokens, setup_test_environment};
-use crate::utils::{MarketplaceError, OrderType};
-use soroban_sdk::testutils::Address as _;
+use crate::tests::utils::{
+    confirm_full_delivery, mint_tokens, setup_test_environment, token_balance, SLOT,
+    STARTING_BALANCE,
+};
+use crate::utils::{
+    MarketplaceError, OrderType, TimeInForce, TradeStatus, ATTESTATION_WINDOW_SECONDS, SLOT_SECONDS,
+};
+use soroban_sdk::testutils::{Address as _, Ledger};
 
 #[test]
 fn test_settle_trade_by_buyer() {
@@ -12,15 +17,30 @@ fn test_settle_trade_by_buyer() {
     mint_tokens(&env, &token_contract, &_admin, &consumer, 10000);
 
     // Create a trade
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Get trade ID
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     let trade = &trade_history.get(0).unwrap();
     let trade_id = trade.trade_id;
 
-    // Settle the trade as buyer
+    // Settle the trade as buyer once delivery is confirmed
+    confirm_full_delivery(&env, &client, trade_id);
     client.settle_trade(&trade_id, &consumer);
 
     // Verify trade still exists (settlement processes payment)
@@ -36,15 +56,30 @@ fn test_settle_trade_by_seller() {
     mint_tokens(&env, &token_contract, &_admin, &consumer, 10000);
 
     // Create a trade
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Get trade ID
-    let trade_history = client.get_trade_history(&producer);
+    let trade_history = client.get_trade_history(&producer, &0u32, &10u32);
     let trade = &trade_history.get(0).unwrap();
     let trade_id = trade.trade_id;
 
-    // Settle the trade as seller
+    // Settle the trade as seller once delivery is confirmed
+    confirm_full_delivery(&env, &client, trade_id);
     client.settle_trade(&trade_id, &producer);
 
     // Verify trade still exists
@@ -60,11 +95,25 @@ fn test_settle_trade_unauthorized() {
     mint_tokens(&env, &token_contract, &_admin, &consumer, 10000);
 
     // Create a trade
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Get trade ID
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     let trade = &trade_history.get(0).unwrap();
     let trade_id = trade.trade_id;
 
@@ -83,23 +132,340 @@ fn test_settle_nonexistent_trade() {
 }
 
 #[test]
-fn test_settlement_with_insufficient_balance() {
-    let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
+fn test_buy_order_with_insufficient_balance() {
+    let (env, client, _admin, _token, producer, _consumer) = setup_test_environment();
+
+    // A consumer with zero balance cannot back a bid
+    let unfunded = soroban_sdk::Address::generate(&env);
+    client.register_consumer(&unfunded);
+
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let result = client.try_place_order(
+        &unfunded,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::PaymentFailed)));
+    assert_eq!(client.get_trade_history(&producer, &0u32, &10u32).len(), 0);
+}
 
-    // Don't mint tokens for consumer - they will have zero balance
+#[test]
+fn test_orders_lock_collateral_and_cancel_releases_it() {
+    let (env, client, _admin, token_contract, producer, consumer) = setup_test_environment();
 
-    // Create a trade
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    // The bid locks its full value, the offer locks a 10% delivery bond
+    let buy_order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &200u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(
+        token_balance(&env, &token_contract, &consumer),
+        STARTING_BALANCE - 12_000
+    );
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &50u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(
+        token_balance(&env, &token_contract, &producer),
+        STARTING_BALANCE - 300
+    );
+
+    // Half the bid is filled; cancelling releases only the unfilled part
+    let buy_order = client.get_order(&buy_order_id);
+    assert_eq!(buy_order.filled_quantity, 50u64);
+    assert_eq!(buy_order.escrow_amount, 9_000u64);
+    assert_eq!(client.get_order(&sell_order_id).escrow_amount, 0u64);
+
+    client.cancel_order(&consumer, &buy_order_id);
+    assert_eq!(
+        token_balance(&env, &token_contract, &consumer),
+        STARTING_BALANCE - 3_000
+    );
+    assert_eq!(client.get_order(&buy_order_id).escrow_amount, 0u64);
+}
 
-    // Get trade ID
-    let trade_history = client.get_trade_history(&consumer);
-    let trade = &trade_history.get(0).unwrap();
-    let trade_id = trade.trade_id;
+#[test]
+fn test_settlement_releases_escrow_to_seller() {
+    let (env, client, _admin, token_contract, producer, consumer) = setup_test_environment();
 
-    // Try to settle - should fail due to insufficient balance
+    client.set_seller_bond_rate(&2_000u32);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // The buyer pays the seller's price; the locked price improvement is returned at once
+    assert_eq!(
+        token_balance(&env, &token_contract, &consumer),
+        STARTING_BALANCE - 5_000
+    );
+    let trade = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap();
+    assert_eq!(trade.total_amount, 5_000u64);
+    assert_eq!(trade.bond_amount, 1_000u64);
+    assert_eq!(trade.status, TradeStatus::Pending);
+
+    confirm_full_delivery(&env, &client, trade.trade_id);
+    client.settle_trade(&trade.trade_id, &consumer);
+    assert_eq!(
+        token_balance(&env, &token_contract, &producer),
+        STARTING_BALANCE + 5_000
+    );
+    assert_eq!(token_balance(&env, &token_contract, &client.address), 0);
+    assert_eq!(
+        client.get_trade(&trade.trade_id).status,
+        TradeStatus::Settled
+    );
+
+    let result = client.try_settle_trade(&trade.trade_id, &producer);
+    assert_eq!(result, Err(Ok(MarketplaceError::TradeAlreadySettled)));
+}
+
+#[test]
+fn test_settlement_requires_attested_delivery() {
+    let (env, client, admin, _token, producer, consumer) = setup_test_environment();
+
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let trade_id = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap()
+        .trade_id;
+
+    let result = client.try_settle_trade(&trade_id, &producer);
+    assert_eq!(result, Err(Ok(MarketplaceError::DeliveryNotConfirmed)));
+
+    // Only the seller's meter or a grid operator may attest
+    let meter = soroban_sdk::Address::generate(&env);
+    let result = client.try_attest_delivery(&meter, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+    let result = client.try_attest_delivery(&consumer, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+
+    // Delivery can only be attested after the slot is over, and never above the traded kWh
+    client.register_meter(&admin, &producer, &meter);
+    let result = client.try_attest_delivery(&meter, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::DeliveryNotFinished)));
+    env.ledger().with_mut(|li| li.timestamp = SLOT + 3_600);
+    let result = client.try_attest_delivery(&meter, &trade_id, &101u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::QuantityOutOfRange)));
+
+    client.attest_delivery(&meter, &trade_id, &100u64);
+    let trade = client.get_trade(&trade_id);
+    assert_eq!(trade.status, TradeStatus::Delivered);
+    assert_eq!(trade.delivered_kwh, 100u64);
+
+    let result = client.try_attest_delivery(&meter, &trade_id, &90u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::TradeAlreadySettled)));
+}
+
+#[test]
+fn test_producer_cannot_bind_its_own_meter() {
+    let (env, client, admin, _token, producer, consumer) = setup_test_environment();
+
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let trade_id = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap()
+        .trade_id;
+
+    // A producer cannot bind a meter for itself, whether its own key or another address
+    let result = client.try_register_meter(&producer, &producer, &producer);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+    let own_meter = soroban_sdk::Address::generate(&env);
+    let result = client.try_register_meter(&producer, &producer, &own_meter);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+
+    // Nor can the admin bind the producer's own key as its meter
+    let result = client.try_register_meter(&admin, &producer, &producer);
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidInput)));
+
+    // So the producer and its self-chosen meter cannot attest its trades
+    env.ledger().with_mut(|li| li.timestamp = SLOT + 3_600);
+    let result = client.try_attest_delivery(&producer, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+    let result = client.try_attest_delivery(&own_meter, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+
+    // A meter bound by a grid operator can
+    let operator = soroban_sdk::Address::generate(&env);
+    client.register_grid_operator(&operator);
+    client.register_meter(&operator, &producer, &own_meter);
+    client.attest_delivery(&own_meter, &trade_id, &100u64);
+    assert_eq!(client.get_trade(&trade_id).status, TradeStatus::Delivered);
+}
+
+#[test]
+fn test_under_delivery_pays_pro_rata_and_penalizes_bond() {
+    let (env, client, _admin, token_contract, producer, consumer) = setup_test_environment();
+
+    // Trade 100 kWh at 50 with a 1_000 delivery bond
+    client.set_seller_bond_rate(&2_000u32);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let trade_id = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap()
+        .trade_id;
+
+    let operator = soroban_sdk::Address::generate(&env);
+    client.register_grid_operator(&operator);
+    env.ledger().with_mut(|li| li.timestamp = SLOT + 3_600);
+    client.attest_delivery(&operator, &trade_id, &80u64);
+    client.settle_trade(&trade_id, &consumer);
+
+    // The seller is paid for 80 kWh and forfeits 20% of the bond to the buyer
+    assert_eq!(
+        token_balance(&env, &token_contract, &producer),
+        STARTING_BALANCE + 4_000 - 200
+    );
+    assert_eq!(
+        token_balance(&env, &token_contract, &consumer),
+        STARTING_BALANCE - 4_000 + 200
+    );
+    assert_eq!(token_balance(&env, &token_contract, &client.address), 0);
+    assert_eq!(client.get_trade(&trade_id).status, TradeStatus::Settled);
+}
+
+#[test]
+fn test_buyer_reclaims_unattested_trade_after_deadline() {
+    let (env, client, _admin, token_contract, producer, consumer) = setup_test_environment();
+
+    // Trade 100 kWh at 50 with a 1_000 delivery bond
+    client.set_seller_bond_rate(&2_000u32);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let trade_id = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap()
+        .trade_id;
+
+    // Nothing can be reclaimed while the meter may still report
+    let deadline = SLOT + SLOT_SECONDS + ATTESTATION_WINDOW_SECONDS;
+    env.ledger().with_mut(|li| li.timestamp = deadline - 1);
+    let result = client.try_reclaim_unattested_trade(&trade_id, &consumer);
+    assert_eq!(result, Err(Ok(MarketplaceError::AttestationWindowOpen)));
+
+    // Past the deadline attestations are refused and only the buyer can reclaim
+    env.ledger().with_mut(|li| li.timestamp = deadline);
+    let operator = soroban_sdk::Address::generate(&env);
+    client.register_grid_operator(&operator);
+    let result = client.try_attest_delivery(&operator, &trade_id, &100u64);
+    assert_eq!(result, Err(Ok(MarketplaceError::AttestationWindowClosed)));
+    let result = client.try_reclaim_unattested_trade(&trade_id, &producer);
+    assert_eq!(result, Err(Ok(MarketplaceError::NotAuthorized)));
+
+    // The buyer gets the payment back and the seller's bond is slashed to the buyer
+    client.reclaim_unattested_trade(&trade_id, &consumer);
+    assert_eq!(
+        token_balance(&env, &token_contract, &consumer),
+        STARTING_BALANCE + 1_000
+    );
+    assert_eq!(
+        token_balance(&env, &token_contract, &producer),
+        STARTING_BALANCE - 1_000
+    );
+    assert_eq!(token_balance(&env, &token_contract, &client.address), 0);
+    assert_eq!(client.get_trade(&trade_id).status, TradeStatus::Refunded);
+
+    let result = client.try_reclaim_unattested_trade(&trade_id, &consumer);
+    assert_eq!(result, Err(Ok(MarketplaceError::TradeAlreadySettled)));
     let result = client.try_settle_trade(&trade_id, &consumer);
-    assert_eq!(result, Err(Ok(MarketplaceError::PaymentFailed)));
+    assert_eq!(result, Err(Ok(MarketplaceError::TradeAlreadySettled)));
 }
 
 #[test]
@@ -110,11 +476,25 @@ fn test_get_trade_details() {
     mint_tokens(&env, &token_contract, &_admin, &consumer, 10000);
 
     // Create a trade
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Get trade details
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 1);
 
     let trade = &trade_history.get(0).unwrap();
@@ -138,19 +518,48 @@ fn test_get_trade_history_multiple_trades() {
     // Create additional producer
     let producer2 = soroban_sdk::Address::generate(&env);
     client.register_producer(&producer2);
+    mint_tokens(&env, &token_contract, &_admin, &producer2, 10000);
 
     // Mint tokens for the consumer
     mint_tokens(&env, &token_contract, &_admin, &consumer, 20000);
 
     // Create multiple trades with same consumer
-    client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
-
-    client.place_order(&producer2, &OrderType::Sell, &200u64, &45u64);
-    client.place_order(&consumer, &OrderType::Buy, &200u64, &55u64);
+    client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    client.place_order(
+        &producer2,
+        &OrderType::Sell,
+        &200u64,
+        &45u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &200u64,
+        &55u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // Verify trade history
-    let trade_history = client.get_trade_history(&consumer);
+    let trade_history = client.get_trade_history(&consumer, &0u32, &10u32);
     assert_eq!(trade_history.len(), 2);
 
     // Verify all trades involve the consumer as buyer
@@ -167,6 +576,6 @@ fn test_get_empty_trade_history() {
     let new_trader = soroban_sdk::Address::generate(&env);
     client.register_consumer(&new_trader);
 
-    let trade_history = client.get_trade_history(&new_trader);
+    let trade_history = client.get_trade_history(&new_trader, &0u32, &10u32);
     assert_eq!(trade_history.len(), 0);
 }
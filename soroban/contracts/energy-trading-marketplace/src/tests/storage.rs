@@ -0,0 +1,186 @@
+#![cfg(test)]
+
+use crate::tests::utils::{confirm_full_delivery, setup_test_environment, SLOT};
+use crate::utils::{
+    MarketKey, OrderStatus, OrderType, TimeInForce, TradeStatus, PERSISTENT_BUMP_AMOUNT,
+};
+use soroban_sdk::testutils::storage::Persistent;
+
+#[test]
+fn test_orders_by_trader_are_paginated() {
+    let (_env, client, _admin, _token, producer, consumer) = setup_test_environment();
+
+    let mut order_ids = [0u64; 5];
+    for (i, order_id) in order_ids.iter_mut().enumerate() {
+        *order_id = client.place_order(
+            &producer,
+            &OrderType::Sell,
+            &100u64,
+            &(50u64 + i as u64),
+            &SLOT,
+            &TimeInForce::GoodTillCancelled,
+        );
+    }
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &10u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    assert_eq!(client.get_trader_order_count(&producer), 5);
+    let first_page = client.get_orders_by_trader(&producer, &0u32, &2u32);
+    assert_eq!(first_page.len(), 2);
+    assert_eq!(first_page.get(0).unwrap().order_id, order_ids[0]);
+    assert_eq!(first_page.get(1).unwrap().order_id, order_ids[1]);
+
+    // The last page is short and pages past the end are empty
+    let last_page = client.get_orders_by_trader(&producer, &4u32, &2u32);
+    assert_eq!(last_page.len(), 1);
+    assert_eq!(last_page.get(0).unwrap().order_id, order_ids[4]);
+    assert_eq!(
+        client.get_orders_by_trader(&producer, &5u32, &2u32).len(),
+        0
+    );
+}
+
+#[test]
+fn test_status_indexes_follow_order_and_trade_lifecycle() {
+    let (env, client, _admin, _token, producer, consumer) = setup_test_environment();
+
+    let sell_order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let cancelled_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &70u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.cancel_order(&producer, &cancelled_id);
+
+    let active = client.get_orders_by_status(&OrderStatus::Active, &0u32, &10u32);
+    assert_eq!(active.len(), 0);
+    let filled = client.get_orders_by_status(&OrderStatus::Filled, &0u32, &10u32);
+    assert_eq!(filled.len(), 2);
+    let cancelled = client.get_orders_by_status(&OrderStatus::Cancelled, &0u32, &10u32);
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(cancelled.get(0).unwrap().order_id, cancelled_id);
+
+    // Trades move from pending through delivered to settled
+    let trade = client
+        .get_trade_history(&producer, &0u32, &10u32)
+        .get(0)
+        .unwrap();
+    assert_eq!(trade.sell_order_id, sell_order_id);
+    assert_eq!(client.get_trader_trade_count(&consumer), 1);
+    assert_eq!(
+        client
+            .get_trades_by_status(&TradeStatus::Pending, &0u32, &10u32)
+            .len(),
+        1
+    );
+
+    confirm_full_delivery(&env, &client, trade.trade_id);
+    client.settle_trade(&trade.trade_id, &consumer);
+    assert_eq!(
+        client
+            .get_trades_by_status(&TradeStatus::Pending, &0u32, &10u32)
+            .len(),
+        0
+    );
+    let settled = client.get_trades_by_status(&TradeStatus::Settled, &0u32, &10u32);
+    assert_eq!(settled.len(), 1);
+    assert_eq!(settled.get(0).unwrap().trade_id, trade.trade_id);
+}
+
+#[test]
+fn test_order_and_trade_entries_have_extended_ttl() {
+    let (env, client, _admin, _token, producer, consumer) = setup_test_environment();
+
+    let order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let trade_id = client
+        .get_trade_history(&consumer, &0u32, &10u32)
+        .get(0)
+        .unwrap()
+        .trade_id;
+
+    env.as_contract(&client.address, || {
+        let order_ttl = env
+            .storage()
+            .persistent()
+            .get_ttl(&MarketKey::Order(order_id));
+        let trade_ttl = env
+            .storage()
+            .persistent()
+            .get_ttl(&MarketKey::Trade(trade_id));
+        assert_eq!(order_ttl, PERSISTENT_BUMP_AMOUNT);
+        assert_eq!(trade_ttl, PERSISTENT_BUMP_AMOUNT);
+    });
+}
+
+#[test]
+fn test_books_are_persistent_and_removed_once_empty() {
+    let (env, client, _admin, _token, producer, consumer) = setup_test_environment();
+
+    let order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    env.as_contract(&client.address, || {
+        let ttl = env.storage().persistent().get_ttl(&MarketKey::Asks(SLOT));
+        assert_eq!(ttl, PERSISTENT_BUMP_AMOUNT);
+        assert!(!env.storage().instance().has(&MarketKey::Asks(SLOT)));
+    });
+
+    // Filling the only resting order empties the book and drops its entry
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(client.get_order(&order_id).status, OrderStatus::Filled);
+    env.as_contract(&client.address, || {
+        assert!(!env.storage().persistent().has(&MarketKey::Asks(SLOT)));
+        assert!(!env.storage().persistent().has(&MarketKey::Bids(SLOT)));
+    });
+}
@@ -1,14 +1,21 @@
 #![cfg(test)]
 
-use crate::tests::utils::setup_test_environment;
-use crate::utils::{MarketplaceError, OrderStatus, OrderType};
-use soroban_sdk::testutils::Address as _;
+use crate::tests::utils::{setup_test_environment, token_balance, SLOT, STARTING_BALANCE};
+use crate::utils::{MarketplaceError, OrderStatus, OrderType, TimeInForce};
+use soroban_sdk::testutils::{Address as _, Ledger};
 
 #[test]
 fn test_place_sell_order() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
-    let order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
+    let order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     let order = client.get_order(&order_id);
     assert_eq!(order.trader, producer);
@@ -22,7 +29,14 @@ fn test_place_sell_order() {
 fn test_place_buy_order() {
     let (_env, client, _admin, _token, _producer, consumer) = setup_test_environment();
 
-    let order_id = client.place_order(&consumer, &OrderType::Buy, &100u64, &60u64);
+    let order_id = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     let order = client.get_order(&order_id);
     assert_eq!(order.trader, consumer);
@@ -37,7 +51,14 @@ fn test_place_order_unregistered_trader() {
     let (env, client, _admin, _token, _producer, _consumer) = setup_test_environment();
 
     let unregistered = soroban_sdk::Address::generate(&env);
-    let result = client.try_place_order(&unregistered, &OrderType::Sell, &100u64, &50u64);
+    let result = client.try_place_order(
+        &unregistered,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
     assert_eq!(result, Err(Ok(MarketplaceError::TraderNotRegistered)));
 }
 
@@ -45,7 +66,14 @@ fn test_place_order_unregistered_trader() {
 fn test_place_order_zero_quantity() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
-    let result = client.try_place_order(&producer, &OrderType::Sell, &0u64, &50u64);
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &0u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
     assert_eq!(result, Err(Ok(MarketplaceError::QuantityOutOfRange)));
 }
 
@@ -53,7 +81,14 @@ fn test_place_order_zero_quantity() {
 fn test_place_order_zero_price() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
-    let result = client.try_place_order(&producer, &OrderType::Sell, &100u64, &0u64);
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &0u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
     assert_eq!(result, Err(Ok(MarketplaceError::PriceOutOfRange)));
 }
 
@@ -61,7 +96,14 @@ fn test_place_order_zero_price() {
 fn test_cancel_order() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
-    let order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
+    let order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
     client.cancel_order(&producer, &order_id);
 
     let order = client.get_order(&order_id);
@@ -72,7 +114,14 @@ fn test_cancel_order() {
 fn test_cancel_order_unauthorized() {
     let (env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
-    let order_id = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
+    let order_id = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     let unauthorized = soroban_sdk::Address::generate(&env);
     let result = client.try_cancel_order(&unauthorized, &order_id);
@@ -92,9 +141,30 @@ fn test_duplicate_order_placement() {
     let (_env, client, _admin, _token, producer, _consumer) = setup_test_environment();
 
     // Place multiple identical orders - should all succeed with different IDs
-    let order_id1 = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    let order_id2 = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
-    let order_id3 = client.place_order(&producer, &OrderType::Sell, &100u64, &50u64);
+    let order_id1 = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let order_id2 = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    let order_id3 = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
 
     // All orders should have unique IDs
     assert_ne!(order_id1, order_id2);
@@ -115,3 +185,202 @@ fn test_duplicate_order_placement() {
     assert_eq!(order2.quantity_kwh, 100u64);
     assert_eq!(order3.quantity_kwh, 100u64);
 }
+
+#[test]
+fn test_place_order_invalid_delivery_slot() {
+    let (env, client, _admin, _token, producer, _consumer) = setup_test_environment();
+
+    // Slots must start on the hour
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &(SLOT + 1),
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidDeliverySlot)));
+
+    // A slot that has already ended can no longer be traded
+    env.ledger().with_mut(|li| li.timestamp = 2 * SLOT);
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidDeliverySlot)));
+
+    // The slot being delivered right now is still open
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &(2 * SLOT),
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert!(result.is_ok());
+}
+
+#[test]
+fn test_good_till_time_orders_expire_lazily() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let result = client.try_place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillTime(0),
+    );
+    assert_eq!(result, Err(Ok(MarketplaceError::InvalidInput)));
+
+    let stale = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &45u64,
+        &SLOT,
+        &TimeInForce::GoodTillTime(600),
+    );
+    let fresh = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+    assert_eq!(client.get_order(&stale).expires_at, 600u64);
+
+    // Once expired, matching skips the cheaper stale offer, expires it and refunds its bond
+    env.ledger().with_mut(|li| li.timestamp = 600);
+    client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &60u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    let stale_order = client.get_order(&stale);
+    assert_eq!(stale_order.status, OrderStatus::Expired);
+    assert_eq!(stale_order.escrow_amount, 0u64);
+    assert_eq!(client.get_order(&fresh).status, OrderStatus::Filled);
+    assert_eq!(
+        token_balance(&env, &token, &producer),
+        STARTING_BALANCE - 500
+    );
+}
+
+#[test]
+fn test_prune_expired_orders() {
+    let (env, client, _admin, token, producer, consumer) = setup_test_environment();
+
+    let gtt = client.place_order(
+        &producer,
+        &OrderType::Sell,
+        &100u64,
+        &50u64,
+        &SLOT,
+        &TimeInForce::GoodTillTime(600),
+    );
+    let gtc = client.place_order(
+        &consumer,
+        &OrderType::Buy,
+        &100u64,
+        &40u64,
+        &SLOT,
+        &TimeInForce::GoodTillCancelled,
+    );
+
+    // Nothing to prune before expiry
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Sell, &SLOT, &0u32, &10u32),
+        0
+    );
+
+    env.ledger().with_mut(|li| li.timestamp = 600);
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Sell, &SLOT, &0u32, &10u32),
+        1
+    );
+    assert_eq!(client.get_order(&gtt).status, OrderStatus::Expired);
+    assert_eq!(
+        client.get_order_book(&OrderType::Sell, &SLOT, &10u32).len(),
+        0
+    );
+    assert_eq!(token_balance(&env, &token, &producer), STARTING_BALANCE);
+
+    // Good-till-cancelled orders still expire once their delivery slot is over
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Buy, &SLOT, &0u32, &10u32),
+        0
+    );
+    env.ledger().with_mut(|li| li.timestamp = SLOT + 3_600);
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Buy, &SLOT, &0u32, &10u32),
+        1
+    );
+    assert_eq!(client.get_order(&gtc).status, OrderStatus::Expired);
+    assert_eq!(token_balance(&env, &token, &consumer), STARTING_BALANCE);
+}
+
+#[test]
+fn test_prune_expired_orders_examines_one_page() {
+    let (env, client, _admin, _token, producer, _consumer) = setup_test_environment();
+
+    // Asks at 50, 51, 52 and 53, of which the first and third expire at 600
+    let mut orders = soroban_sdk::Vec::new(&env);
+    for (price, expiry) in [(50u64, 600u64), (51, 0), (52, 600), (53, 0)] {
+        let time_in_force = if expiry == 0 {
+            TimeInForce::GoodTillCancelled
+        } else {
+            TimeInForce::GoodTillTime(expiry)
+        };
+        orders.push_back(client.place_order(
+            &producer,
+            &OrderType::Sell,
+            &100u64,
+            &price,
+            &SLOT,
+            &time_in_force,
+        ));
+    }
+    env.ledger().with_mut(|li| li.timestamp = 600);
+
+    // A page of two only reaches the first expired order
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Sell, &SLOT, &0u32, &2u32),
+        1
+    );
+    assert_eq!(
+        client.get_order(&orders.get(2).unwrap()).status,
+        OrderStatus::Active
+    );
+
+    // The next page starts at offset + limit - pruned
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Sell, &SLOT, &1u32, &2u32),
+        1
+    );
+    assert_eq!(
+        client.get_order(&orders.get(2).unwrap()).status,
+        OrderStatus::Expired
+    );
+    let book = client.get_order_book(&OrderType::Sell, &SLOT, &10u32);
+    assert_eq!(book.len(), 2);
+    assert_eq!(book.get(0).unwrap().order_id, orders.get(1).unwrap());
+    assert_eq!(book.get(1).unwrap().order_id, orders.get(3).unwrap());
+
+    // Pages past the end of the book do nothing
+    assert_eq!(
+        client.prune_expired_orders(&OrderType::Sell, &SLOT, &5u32, &2u32),
+        0
+    );
+}
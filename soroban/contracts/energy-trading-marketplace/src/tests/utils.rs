@@ -1,7 +1,17 @@
 #![cfg(test)]
 
+use crate::utils::SLOT_SECONDS;
 use crate::{EnergyTradingMarketplace, EnergyTradingMarketplaceClient};
-use soroban_sdk::{testutils::Address as _, Address, Env};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, Env,
+};
+
+/// Tokens minted to each trader created by the test helpers
+pub const STARTING_BALANCE: i128 = 1_000_000;
+
+/// Delivery slot used by tests that do not care about slot handling
+pub const SLOT: u64 = 3_600;
 
 pub fn setup_test_environment() -> (
     Env,
@@ -25,9 +35,11 @@ pub fn setup_test_environment() -> (
     // Initialize contract
     client.initialize(&admin, &token_contract, &10u64, &1000u64);
 
-    // Register traders
+    // Register traders and fund their order collateral
     client.register_producer(&producer);
     client.register_consumer(&consumer);
+    mint_tokens(&env, &token_contract, &admin, &producer, STARTING_BALANCE);
+    mint_tokens(&env, &token_contract, &admin, &consumer, STARTING_BALANCE);
 
     (env, client, admin, token_contract, producer, consumer)
 }
@@ -48,3 +60,17 @@ pub fn mint_tokens(
     let token = token::StellarAssetClient::new(env, token_address);
     token.mint(to, &amount);
 }
+
+pub fn token_balance(env: &Env, token_address: &Address, of: &Address) -> i128 {
+    soroban_sdk::token::Client::new(env, token_address).balance(of)
+}
+
+/// Have a grid operator attest full delivery of a trade once its slot has ended
+pub fn confirm_full_delivery(env: &Env, client: &EnergyTradingMarketplaceClient, trade_id: u64) {
+    let trade = client.get_trade(&trade_id);
+    let operator = Address::generate(env);
+    client.register_grid_operator(&operator);
+    env.ledger()
+        .with_mut(|li| li.timestamp = trade.delivery_slot + SLOT_SECONDS);
+    client.attest_delivery(&operator, &trade_id, &trade.quantity_kwh);
+}
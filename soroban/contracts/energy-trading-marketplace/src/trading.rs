@@ -1,128 +1,355 @@
+use crate::auction;
+use crate::certificates;
+use crate::escrow;
+use crate::storage;
 use crate::utils::*;
-use soroban_sdk::{symbol_short, Address, Env, Map, Vec};
+use soroban_sdk::{symbol_short, Address, Env, Vec};
+
+/// Place an order built with `new_order`, locking its collateral and matching it
+pub fn place_order(env: &Env, mut order: EnergyOrder) -> Result<u64, MarketplaceError> {
+    validate_delivery_slot(env, order.delivery_slot)?;
+    // Auction slots only take sealed orders
+    if auction::has_auction(env, order.delivery_slot) {
+        return Err(MarketplaceError::SlotInAuction);
+    }
+
+    order.expires_at = match order.time_in_force {
+        TimeInForce::GoodTillTime(expires_at) => {
+            if expires_at <= env.ledger().timestamp() {
+                return Err(MarketplaceError::InvalidInput);
+            }
+            expires_at
+        }
+        _ => 0,
+    };
+
+    // Certificates back sells and green-only applies to buys; a premium needs one of them
+    let green_terms_valid = match order.order_type {
+        OrderType::Sell => {
+            !order.green_only && (order.origin.rec_id().is_some() || order.green_premium == 0)
+        }
+        OrderType::Buy => {
+            order.origin.rec_id().is_none() && (order.green_only || order.green_premium == 0)
+        }
+    };
+    if !green_terms_valid {
+        return Err(MarketplaceError::InvalidInput);
+    }
+
+    // Lock the order's collateral before it can reach the book; buyers also cover their
+    // maximum green premium
+    let collateral_price = match order.order_type {
+        OrderType::Buy => order.price_per_kwh + order.green_premium,
+        OrderType::Sell => order.price_per_kwh,
+    };
+    order.escrow_amount =
+        escrow::required_collateral(env, &order.order_type, order.quantity_kwh, collateral_price);
+    escrow::lock(env, &order.trader, order.escrow_amount)?;
+    if let Some(rec_id) = order.origin.rec_id() {
+        let custody_id = certificates::lock(env, &order.trader, rec_id, order.quantity_kwh)?;
+        order.origin = EnergyOrigin::Certified(custody_id);
+    }
+    storage::save_order(env, &order);
+
+    // A fill-or-kill order that cannot fill completely never touches the book
+    if order.time_in_force == TimeInForce::FillOrKill
+        && fillable_quantity(env, &order) < order.quantity_kwh
+    {
+        close_order(env, &mut order, OrderStatus::Cancelled)?;
+        return Ok(order.order_id);
+    }
+
+    // Match against the opposite side, then rest whatever is left in the book
+    match_order(env, order.order_id)?;
+
+    Ok(order.order_id)
+}
+
+/// Check that a delivery slot starts on the hour and has not ended yet
+pub fn validate_delivery_slot(env: &Env, delivery_slot: u64) -> Result<(), MarketplaceError> {
+    if !delivery_slot.is_multiple_of(SLOT_SECONDS)
+        || delivery_slot + SLOT_SECONDS <= env.ledger().timestamp()
+    {
+        return Err(MarketplaceError::InvalidDeliverySlot);
+    }
+    Ok(())
+}
 
-/// Place an order
-pub fn place_order(
+/// Build a new active good-till-cancelled order without green terms
+pub fn new_order(
     env: &Env,
     trader: Address,
     order_type: OrderType,
     quantity_kwh: u64,
     price_per_kwh: u64,
-) -> Result<u64, MarketplaceError> {
-    let order_id = get_next_order_id(env);
-
-    let order = EnergyOrder {
-        order_id,
-        trader: trader.clone(),
-        order_type: order_type.clone(),
+    delivery_slot: u64,
+    escrow_amount: u64,
+) -> EnergyOrder {
+    EnergyOrder {
+        order_id: get_next_order_id(env),
+        trader,
+        order_type,
         quantity_kwh,
+        filled_quantity: 0,
         price_per_kwh,
+        escrow_amount,
+        delivery_slot,
+        time_in_force: TimeInForce::GoodTillCancelled,
+        expires_at: 0,
+        origin: EnergyOrigin::Unspecified,
+        green_only: false,
+        green_premium: 0,
         timestamp: env.ledger().timestamp(),
         status: OrderStatus::Active,
-    };
-
-    // Store order
-    let mut orders: Map<u64, EnergyOrder> = env
-        .storage()
-        .instance()
-        .get(&DataKey::Orders)
-        .unwrap_or_else(|| Map::new(env));
-
-    orders.set(order_id, order.clone());
-    env.storage().instance().set(&DataKey::Orders, &orders);
-
-    // Try to match immediately
-    match_order(env, order_id)?;
-
-    Ok(order_id)
+    }
 }
 
-/// Match an order with existing orders
+/// Match an order against the opposite side of the book until it is filled or no price crosses
 pub fn match_order(env: &Env, order_id: u64) -> Result<Vec<u64>, MarketplaceError> {
-    let orders: Map<u64, EnergyOrder> = env.storage().instance().get(&DataKey::Orders).unwrap();
-    let new_order = orders
-        .get(order_id)
-        .ok_or(MarketplaceError::OrderNotFound)?;
-
-    if new_order.status != OrderStatus::Active {
-        return Ok(Vec::new(env));
-    }
+    let mut order = storage::load_order(env, order_id)?;
 
     let mut matched_trades = Vec::new(env);
+    if order.status != OrderStatus::Active {
+        return Ok(matched_trades);
+    }
 
-    // Find matching orders of opposite type
-    let opposite_type = match new_order.order_type {
+    let opposite_type = match order.order_type {
         OrderType::Buy => OrderType::Sell,
         OrderType::Sell => OrderType::Buy,
     };
+    let book = get_book(env, &opposite_type, order.delivery_slot);
+    let mut remaining_book = Vec::new(env);
+
+    // The opposite book is sorted best price first, oldest first within a price, so matching
+    // stops at the first resting order whose price no longer crosses
+    let mut position = 0;
+    while position < book.len() {
+        let resting_id = book.get_unchecked(position);
+        let mut resting = storage::load_order(env, resting_id)?;
+        // Expired orders are dropped from the book as matching reaches them
+        if is_expired(env, &resting) {
+            close_order(env, &mut resting, OrderStatus::Expired)?;
+            position += 1;
+            continue;
+        }
+        if remaining(&order) == 0 || !prices_cross(&order, &resting) {
+            break;
+        }
+        position += 1;
+        // Self-trade prevention and green terms: skip resting orders this one cannot trade with
+        if resting.trader == order.trader || !green_terms_match(&order, &resting) {
+            remaining_book.push_back(resting_id);
+            continue;
+        }
 
-    for (match_order_id, match_order) in orders.iter() {
-        if match_order_id == order_id || match_order.status != OrderStatus::Active {
+        let quantity = match_quantity(&order, &resting, remaining(&order));
+        if quantity == 0 {
+            remaining_book.push_back(resting_id);
             continue;
         }
+        let price = resting.price_per_kwh;
+        let trade_id = if order.order_type == OrderType::Buy {
+            execute_trade(env, &mut order, &mut resting, quantity, price)?
+        } else {
+            execute_trade(env, &mut resting, &mut order, quantity, price)?
+        };
+        matched_trades.push_back(trade_id);
+
+        storage::save_order(env, &resting);
+        if resting.status == OrderStatus::Active {
+            remaining_book.push_back(resting_id);
+        }
+    }
+    remaining_book.append(&book.slice(position..));
 
-        if match_order.order_type == opposite_type && can_orders_match(&new_order, &match_order) {
-            let trade_id = execute_trade(env, &new_order, &match_order)?;
-            matched_trades.push_back(trade_id);
-            break;
+    set_book(env, &opposite_type, order.delivery_slot, &remaining_book);
+
+    // Immediate-or-cancel orders never rest; their unfilled part is cancelled
+    if order.status == OrderStatus::Active && order.time_in_force == TimeInForce::ImmediateOrCancel
+    {
+        close_order(env, &mut order, OrderStatus::Cancelled)?;
+    } else {
+        storage::save_order(env, &order);
+        if order.status == OrderStatus::Active {
+            insert_into_book(env, &order);
         }
     }
 
     Ok(matched_trades)
 }
 
-/// Check if two orders can match
-fn can_orders_match(buy_order: &EnergyOrder, sell_order: &EnergyOrder) -> bool {
-    let (buyer, seller) = if buy_order.order_type == OrderType::Buy {
-        (buy_order, sell_order)
-    } else {
-        (sell_order, buy_order)
+/// Whether an order has passed its expiry time or its delivery slot has ended
+pub fn is_expired(env: &Env, order: &EnergyOrder) -> bool {
+    let now = env.ledger().timestamp();
+    (order.expires_at != 0 && now >= order.expires_at) || now >= order.delivery_slot + SLOT_SECONDS
+}
+
+/// Quantity an incoming order could fill against the current book, up to its own size
+fn fillable_quantity(env: &Env, order: &EnergyOrder) -> u64 {
+    let opposite_type = match order.order_type {
+        OrderType::Buy => OrderType::Sell,
+        OrderType::Sell => OrderType::Buy,
+    };
+
+    // Walk the book the same way matching does
+    let mut unfilled = order.quantity_kwh;
+    for resting_id in get_book(env, &opposite_type, order.delivery_slot).iter() {
+        let resting = storage::load_order(env, resting_id).unwrap();
+        if unfilled == 0 || !prices_cross(order, &resting) {
+            break;
+        }
+        if resting.trader != order.trader
+            && green_terms_match(order, &resting)
+            && !is_expired(env, &resting)
+        {
+            unfilled -= match_quantity(order, &resting, unfilled);
+        }
+    }
+    order.quantity_kwh - unfilled
+}
+
+/// Whether the green terms of two crossing orders allow them to trade
+fn green_terms_match(incoming: &EnergyOrder, resting: &EnergyOrder) -> bool {
+    let (buy, sell) = match incoming.order_type {
+        OrderType::Buy => (incoming, resting),
+        OrderType::Sell => (resting, incoming),
+    };
+    (sell.origin.rec_id().is_some() || !buy.green_only) && sell.green_premium <= buy.green_premium
+}
+
+/// Quantity two orders can trade; certified supply trades in whole MWh
+fn match_quantity(incoming: &EnergyOrder, resting: &EnergyOrder, unfilled: u64) -> u64 {
+    let quantity = unfilled.min(remaining(resting));
+    let certified = match incoming.order_type {
+        OrderType::Buy => resting.origin.rec_id().is_some(),
+        OrderType::Sell => incoming.origin.rec_id().is_some(),
     };
+    if certified {
+        quantity - quantity % KWH_PER_MWH
+    } else {
+        quantity
+    }
+}
+
+/// Take an order out of trading, releasing whatever collateral still backs it
+fn close_order(
+    env: &Env,
+    order: &mut EnergyOrder,
+    status: OrderStatus,
+) -> Result<(), MarketplaceError> {
+    escrow::release(env, &order.trader, order.escrow_amount)?;
+    if let Some(rec_id) = order.origin.rec_id() {
+        certificates::release(env, rec_id, &order.trader, remaining(order))?;
+    }
+    order.escrow_amount = 0;
+    order.status = status;
+    storage::save_order(env, order);
+
+    if order.status == OrderStatus::Expired {
+        env.events().publish(
+            (symbol_short!("expired"), order.trader.clone()),
+            order.order_id,
+        );
+    }
+    Ok(())
+}
+
+/// Expire the expired orders among `limit` resting orders from position `offset` of one side
+/// of a slot's book and refund their escrow
+///
+/// Anyone may call this to clear stale orders; returns how many were pruned. Only the orders in
+/// the page are loaded, and the book is rewritten only when something was pruned. The next page
+/// starts at `offset + limit - pruned`.
+pub fn prune_expired_orders(
+    env: &Env,
+    order_type: OrderType,
+    delivery_slot: u64,
+    offset: u32,
+    limit: u32,
+) -> Result<u32, MarketplaceError> {
+    let book = get_book(env, &order_type, delivery_slot);
+    let start = offset.min(book.len());
+    let end = offset.saturating_add(limit).min(book.len());
+    let mut remaining_book = book.slice(0..start);
+    let mut pruned = 0;
+
+    for resting_id in book.slice(start..end).iter() {
+        let mut resting = storage::load_order(env, resting_id)?;
+        if is_expired(env, &resting) {
+            close_order(env, &mut resting, OrderStatus::Expired)?;
+            pruned += 1;
+        } else {
+            remaining_book.push_back(resting_id);
+        }
+    }
+
+    if pruned > 0 {
+        remaining_book.append(&book.slice(end..));
+        set_book(env, &order_type, delivery_slot, &remaining_book);
+    }
+    Ok(pruned)
+}
+
+/// Quantity of an order still open for matching
+pub fn remaining(order: &EnergyOrder) -> u64 {
+    order.quantity_kwh - order.filled_quantity
+}
+
+/// Check if an incoming order's price reaches a resting order's price
+fn prices_cross(incoming: &EnergyOrder, resting: &EnergyOrder) -> bool {
+    match incoming.order_type {
+        OrderType::Buy => incoming.price_per_kwh >= resting.price_per_kwh,
+        OrderType::Sell => incoming.price_per_kwh <= resting.price_per_kwh,
+    }
+}
 
-    // Price matching: buy price >= sell price
-    buyer.price_per_kwh >= seller.price_per_kwh && buyer.trader != seller.trader
+/// Record a fill against an order, marking it filled once nothing remains
+fn fill(order: &mut EnergyOrder, quantity: u64) {
+    order.filled_quantity += quantity;
+    if remaining(order) == 0 {
+        order.status = OrderStatus::Filled;
+    }
 }
 
-/// Execute a trade between matching orders
-fn execute_trade(
+/// Execute a trade between matching orders, moving both sides' escrow onto the trade
+pub fn execute_trade(
     env: &Env,
-    order1: &EnergyOrder,
-    order2: &EnergyOrder,
+    buy_order: &mut EnergyOrder,
+    sell_order: &mut EnergyOrder,
+    quantity: u64,
+    price: u64,
 ) -> Result<u64, MarketplaceError> {
     let trade_id = get_next_trade_id(env);
+    let green_premium = sell_order.green_premium;
+    let total_amount = quantity * (price + green_premium);
 
-    let (buyer, seller) = if order1.order_type == OrderType::Buy {
-        (order1, order2)
-    } else {
-        (order2, order1)
-    };
+    // The buyer locked their limit price and premium; any improvement goes straight back
+    let buyer_share = escrow::take_share(buy_order, quantity);
+    escrow::release(env, &buy_order.trader, buyer_share - total_amount)?;
+    let bond_amount = escrow::take_share(sell_order, quantity);
 
-    let quantity = buyer.quantity_kwh.min(seller.quantity_kwh);
-    let price = seller.price_per_kwh; // Use seller's price
-    let total_amount = quantity * price;
+    fill(buy_order, quantity);
+    fill(sell_order, quantity);
 
     let trade = Trade {
         trade_id,
-        buyer: buyer.trader.clone(),
-        seller: seller.trader.clone(),
+        buyer: buy_order.trader.clone(),
+        seller: sell_order.trader.clone(),
+        buy_order_id: buy_order.order_id,
+        sell_order_id: sell_order.order_id,
         quantity_kwh: quantity,
         price_per_kwh: price,
         total_amount,
+        bond_amount,
+        delivered_kwh: 0,
+        origin: sell_order.origin.clone(),
+        green_premium,
+        delivery_slot: sell_order.delivery_slot,
         timestamp: env.ledger().timestamp(),
+        status: TradeStatus::Pending,
     };
 
-    // Store trade
-    let mut trades: Map<u64, Trade> = env
-        .storage()
-        .instance()
-        .get(&DataKey::Trades)
-        .unwrap_or_else(|| Map::new(env));
-
-    trades.set(trade_id, trade);
-    env.storage().instance().set(&DataKey::Trades, &trades);
-
-    // Mark orders as filled
-    mark_orders_filled(env, order1.order_id, order2.order_id)?;
+    storage::save_trade(env, &trade);
 
     // Emit trade event
     env.events().publish(
@@ -133,30 +360,9 @@ fn execute_trade(
     Ok(trade_id)
 }
 
-/// Mark orders as filled
-fn mark_orders_filled(env: &Env, order_id1: u64, order_id2: u64) -> Result<(), MarketplaceError> {
-    let mut orders: Map<u64, EnergyOrder> = env.storage().instance().get(&DataKey::Orders).unwrap();
-
-    if let Some(mut order1) = orders.get(order_id1) {
-        order1.status = OrderStatus::Filled;
-        orders.set(order_id1, order1);
-    }
-
-    if let Some(mut order2) = orders.get(order_id2) {
-        order2.status = OrderStatus::Filled;
-        orders.set(order_id2, order2);
-    }
-
-    env.storage().instance().set(&DataKey::Orders, &orders);
-    Ok(())
-}
-
 /// Cancel an order
 pub fn cancel_order(env: &Env, trader: Address, order_id: u64) -> Result<(), MarketplaceError> {
-    let mut orders: Map<u64, EnergyOrder> = env.storage().instance().get(&DataKey::Orders).unwrap();
-    let mut order = orders
-        .get(order_id)
-        .ok_or(MarketplaceError::OrderNotFound)?;
+    let mut order = storage::load_order(env, order_id)?;
 
     if order.trader != trader {
         return Err(MarketplaceError::NotAuthorized);
@@ -166,9 +372,14 @@ pub fn cancel_order(env: &Env, trader: Address, order_id: u64) -> Result<(), Mar
         return Err(MarketplaceError::InvalidInput);
     }
 
-    order.status = OrderStatus::Cancelled;
-    orders.set(order_id, order);
-    env.storage().instance().set(&DataKey::Orders, &orders);
+    // Revealed auction orders stay committed until the auction clears
+    if auction::has_auction(env, order.delivery_slot) {
+        return Err(MarketplaceError::SlotInAuction);
+    }
+
+    // Release whatever collateral still backs the unfilled quantity
+    close_order(env, &mut order, OrderStatus::Cancelled)?;
+    remove_from_book(env, &order);
 
     env.events()
         .publish((symbol_short!("cancelled"), trader), order_id);
@@ -178,13 +389,82 @@ pub fn cancel_order(env: &Env, trader: Address, order_id: u64) -> Result<(), Mar
 
 /// Get order details
 pub fn get_order(env: &Env, order_id: u64) -> Result<EnergyOrder, MarketplaceError> {
-    let orders: Map<u64, EnergyOrder> = env
-        .storage()
-        .instance()
-        .get(&DataKey::Orders)
-        .unwrap_or_else(|| Map::new(env));
+    storage::load_order(env, order_id)
+}
+
+/// Get the resting orders on one side of a slot's book, best price first
+pub fn get_order_book(
+    env: &Env,
+    order_type: OrderType,
+    delivery_slot: u64,
+    limit: u32,
+) -> Vec<EnergyOrder> {
+    let mut result = Vec::new(env);
+    for order_id in get_book(env, &order_type, delivery_slot).iter() {
+        if result.len() >= limit {
+            break;
+        }
+        result.push_back(storage::load_order(env, order_id).unwrap());
+    }
+    result
+}
+
+fn book_key(order_type: &OrderType, delivery_slot: u64) -> MarketKey {
+    match order_type {
+        OrderType::Buy => MarketKey::Bids(delivery_slot),
+        OrderType::Sell => MarketKey::Asks(delivery_slot),
+    }
+}
+
+pub fn get_book(env: &Env, order_type: &OrderType, delivery_slot: u64) -> Vec<u64> {
+    storage::get_persistent(env, &book_key(order_type, delivery_slot))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Store one side of a slot's book, removing the entry once the book is empty
+fn set_book(env: &Env, order_type: &OrderType, delivery_slot: u64, book: &Vec<u64>) {
+    let key = book_key(order_type, delivery_slot);
+    if book.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        storage::set_persistent(env, &key, book);
+    }
+}
 
-    orders.get(order_id).ok_or(MarketplaceError::OrderNotFound)
+/// Whether `order` ranks ahead of `other`: better price first, then earlier order ID
+pub fn has_priority(order: &EnergyOrder, other: &EnergyOrder) -> bool {
+    if order.price_per_kwh != other.price_per_kwh {
+        return match order.order_type {
+            OrderType::Buy => order.price_per_kwh > other.price_per_kwh,
+            OrderType::Sell => order.price_per_kwh < other.price_per_kwh,
+        };
+    }
+    order.order_id < other.order_id
+}
+
+/// Insert an order at its priority position, binary searching the sorted book
+fn insert_into_book(env: &Env, order: &EnergyOrder) {
+    let mut book = get_book(env, &order.order_type, order.delivery_slot);
+    let (mut low, mut high) = (0, book.len());
+    while low < high {
+        let middle = (low + high) / 2;
+        let resting = storage::load_order(env, book.get_unchecked(middle)).unwrap();
+        if has_priority(order, &resting) {
+            high = middle;
+        } else {
+            low = middle + 1;
+        }
+    }
+    book.insert(low, order.order_id);
+    set_book(env, &order.order_type, order.delivery_slot, &book);
+}
+
+fn remove_from_book(env: &Env, order: &EnergyOrder) {
+    let mut book = get_book(env, &order.order_type, order.delivery_slot);
+    if let Some(index) = book.first_index_of(order.order_id) {
+        book.remove(index);
+        set_book(env, &order.order_type, order.delivery_slot, &book);
+    }
 }
 
 fn get_next_order_id(env: &Env) -> u64 {
@@ -1,4 +1,4 @@
-use soroban_sdk::{contracterror, contracttype, Address, Env, Map, Vec};
+use soroban_sdk::{contracterror, contracttype, Address, BytesN, Vec};
 
 #[contracttype]
 #[derive(Copy, Clone)]
@@ -9,13 +9,62 @@ pub enum DataKey {
     GridOperators = 2,
     Producers = 3,
     Consumers = 4,
-    Orders = 5,
-    Trades = 6,
     NextOrderId = 7,
     NextTradeId = 8,
     TokenContract = 9,
+    SellerBondRate = 10,
+    NextSealedBidId = 11,
+    RecContract = 12,
 }
 
+/// Storage keys for entries identified by a delivery slot, ID or index
+#[contracttype]
+#[derive(Clone)]
+pub enum MarketKey {
+    Bids(u64),      // Delivery slot -> resting buy order IDs, best first (persistent)
+    Asks(u64),      // Delivery slot -> resting sell order IDs, best first (persistent)
+    Auction(u64),   // Delivery slot -> Auction (persistent)
+    SealedBid(u64), // Sealed bid ID -> SealedBid (persistent, removed once cleared)
+    Meter(Address), // Producer -> meter allowed to attest its deliveries (persistent)
+    Order(u64),     // Order ID -> EnergyOrder (persistent)
+    Trade(u64),     // Trade ID -> Trade (persistent)
+    IndexLength(Index),
+    IndexEntry(Index, u32),    // Position -> order or trade ID
+    IndexPosition(Index, u64), // Order or trade ID -> position
+}
+
+/// Paginated lists of order or trade IDs kept in persistent storage
+#[contracttype]
+#[derive(Clone)]
+pub enum Index {
+    TraderOrders(Address),
+    TraderTrades(Address),
+    OrderStatus(OrderStatus),
+    TradeStatus(TradeStatus),
+}
+
+/// Ledgers per day at a 5 second close time
+pub const DAY_IN_LEDGERS: u32 = 17_280;
+
+/// Persistent entries are extended to this TTL whenever they are read or written
+pub const PERSISTENT_BUMP_AMOUNT: u32 = 30 * DAY_IN_LEDGERS;
+
+/// Persistent entries below this TTL are extended on access
+pub const PERSISTENT_LIFETIME_THRESHOLD: u32 = PERSISTENT_BUMP_AMOUNT - DAY_IN_LEDGERS;
+
+/// Length of a delivery slot in seconds; slots start on the hour
+pub const SLOT_SECONDS: u64 = 3_600;
+
+/// Time after a delivery slot ends for its delivery to be attested; after that the buyer can
+/// reclaim an unattested trade's payment
+pub const ATTESTATION_WINDOW_SECONDS: u64 = 24 * SLOT_SECONDS;
+
+/// Renewable energy certificates are denominated in MWh
+pub const KWH_PER_MWH: u64 = 1_000;
+
+/// Default seller delivery bond, in basis points of the order value
+pub const DEFAULT_SELLER_BOND_BPS: u32 = 1_000;
+
 #[contracttype]
 #[derive(Clone, Debug, PartialEq)]
 pub struct EnergyOrder {
@@ -23,7 +72,15 @@ pub struct EnergyOrder {
     pub trader: Address,
     pub order_type: OrderType,
     pub quantity_kwh: u64,
+    pub filled_quantity: u64,
     pub price_per_kwh: u64,
+    pub escrow_amount: u64, // Funds (buy) or delivery bond (sell) still locked for the unfilled quantity
+    pub delivery_slot: u64, // Start of the delivery hour
+    pub time_in_force: TimeInForce,
+    pub expires_at: u64, // 0 if the order only expires with its delivery slot
+    pub origin: EnergyOrigin, // Sell only: certificate held in custody for the order
+    pub green_only: bool, // Buy only: match certified supply only
+    pub green_premium: u64, // Per kWh: asked on certified sells, maximum accepted on buys
     pub timestamp: u64,
     pub status: OrderStatus,
 }
@@ -34,10 +91,18 @@ pub struct Trade {
     pub trade_id: u64,
     pub buyer: Address,
     pub seller: Address,
+    pub buy_order_id: u64,
+    pub sell_order_id: u64,
     pub quantity_kwh: u64,
     pub price_per_kwh: u64,
-    pub total_amount: u64,
+    pub total_amount: u64,  // Buyer's payment, held in escrow until settlement
+    pub bond_amount: u64,   // Seller's delivery bond backing this trade
+    pub delivered_kwh: u64, // Attested by the seller's meter or a grid operator
+    pub origin: EnergyOrigin,
+    pub green_premium: u64, // Per kWh, included in total_amount
+    pub delivery_slot: u64,
     pub timestamp: u64,
+    pub status: TradeStatus,
 }
 
 #[contracttype]
@@ -55,6 +120,77 @@ pub enum OrderStatus {
     Active = 0,
     Filled = 1,
     Cancelled = 2,
+    Expired = 3,
+}
+
+/// Whether supply is backed by a renewable energy certificate
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum EnergyOrigin {
+    Unspecified,
+    Certified(BytesN<32>), // REC ID in the certificate tracker
+}
+
+impl EnergyOrigin {
+    pub fn rec_id(&self) -> Option<&BytesN<32>> {
+        match self {
+            EnergyOrigin::Unspecified => None,
+            EnergyOrigin::Certified(rec_id) => Some(rec_id),
+        }
+    }
+}
+
+/// How long an order stays in the book
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum TimeInForce {
+    GoodTillCancelled,
+    GoodTillTime(u64), // Expiry timestamp
+    ImmediateOrCancel, // Fill what crosses now, cancel the rest
+    FillOrKill,        // Fill completely now or cancel entirely
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum TradeStatus {
+    Pending = 0,
+    Delivered = 1,
+    Settled = 2,
+    Refunded = 3, // Never attested; payment and bond went back to the buyer
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Auction {
+    pub delivery_slot: u64,
+    pub operator: Address,
+    pub bidding_closes_at: u64, // Sealed orders are accepted until this time
+    pub reveal_closes_at: u64,  // Sealed orders must be revealed before this time
+    pub sealed_bids: Vec<u64>,
+    pub orders: Vec<u64>, // Revealed order IDs
+    pub status: AuctionStatus,
+    pub clearing_price: u64,
+    pub cleared_quantity: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SealedBid {
+    pub bid_id: u64,
+    pub trader: Address,
+    pub delivery_slot: u64,
+    pub commitment: BytesN<32>, // sha256(trader, slot, order type, quantity, price, salt)
+    pub deposit: u64,           // Locked up front, must cover the revealed order's collateral
+    pub revealed: bool,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+#[repr(u32)]
+pub enum AuctionStatus {
+    Open = 0,
+    Cleared = 1,
 }
 
 #[contracttype]
@@ -80,20 +216,16 @@ pub enum MarketplaceError {
     TraderNotRegistered = 8,
     QuantityOutOfRange = 9,
     PaymentFailed = 10,
-}
-
-pub fn get_trades_by_trader(env: &Env, trader: Address) -> Result<Vec<Trade>, MarketplaceError> {
-    let trades: Map<u64, Trade> = env
-        .storage()
-        .instance()
-        .get(&DataKey::Trades)
-        .unwrap_or_else(|| Map::new(env));
-
-    let mut trader_trades = Vec::new(env);
-    for (_, trade) in trades.iter() {
-        if trade.buyer == trader || trade.seller == trader {
-            trader_trades.push_back(trade);
-        }
-    }
-    Ok(trader_trades)
+    TradeAlreadySettled = 11,
+    InvalidDeliverySlot = 12,
+    SlotInAuction = 13,
+    AuctionNotFound = 14,
+    AuctionPhaseError = 15,
+    CommitmentMismatch = 16,
+    DeliveryNotConfirmed = 17,
+    DeliveryNotFinished = 18,
+    CertificateInvalid = 19,
+    RecContractNotSet = 20,
+    AttestationWindowOpen = 21,
+    AttestationWindowClosed = 22,
 }
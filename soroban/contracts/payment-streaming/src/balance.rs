@@ -1,6 +1,6 @@
 use crate::{
     error::PaymentStreamingError,
-    stream::{Stream, StreamState},
+    stream::{elapsed_units, Stream, StreamState},
 };
 use soroban_sdk::{panic_with_error, BytesN, Env};
 
@@ -11,8 +11,7 @@ pub fn get_stream_balance(env: &Env, stream_id: BytesN<32>) -> StreamState {
         .get(&stream_id)
         .unwrap_or_else(|| panic_with_error!(env, PaymentStreamingError::StreamNotFound));
 
-    let current_time = env.ledger().timestamp();
-    let elapsed = current_time - stream.start_time;
+    let elapsed = elapsed_units(env, &stream);
     let available = if stream.is_active {
         calculate_available(&stream, elapsed)
     } else {
@@ -24,6 +23,8 @@ pub fn get_stream_balance(env: &Env, stream_id: BytesN<32>) -> StreamState {
         withdrawn: stream.withdrawn,
         available,
         total: stream.total_amount,
+        ledgers_elapsed: env.ledger().sequence() - stream.start_ledger,
+        seconds_elapsed: env.ledger().timestamp() - stream.start_time,
     }
 }
 
@@ -32,8 +32,8 @@ pub fn open_channel(
         .storage()
         .instance()
         .get(&CHANNEL_KEY)
-        .unwrap_or(BytesN::from_array(&env, &[0; 32]));
-    let channel_id = increment_bytesn(&env, current_channel_id);
+        .unwrap_or(BytesN::from_array(env, &[0; 32]));
+    let channel_id = increment_bytesn(env, current_channel_id);
     let channel = PaymentChannel {
         channel_id: channel_id.clone(),
         party_a: sender,
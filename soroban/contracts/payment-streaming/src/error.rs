@@ -16,5 +16,6 @@ pub enum PaymentStreamingError {
     Channelisclosed = 11,
     Invalidamount = 12,
     InsufficientFunds = 13,
-    InvalidDeposit = 14
-}
\ No newline at end of file
+    InvalidDeposit = 14,
+    BatchTooLarge = 15,
+}
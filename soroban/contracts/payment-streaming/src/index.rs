@@ -0,0 +1,96 @@
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+#[contracttype]
+#[derive(Clone)]
+pub enum StreamIndexKey {
+    Sender(Address),
+    Recipient(Address),
+}
+
+// Each index is a count plus one entry per position, so adding or removing a
+// stream touches a fixed number of entries however many streams are indexed
+#[contracttype]
+#[derive(Clone)]
+enum IndexEntryKey {
+    Count(StreamIndexKey),
+    Entry(StreamIndexKey, u32),
+    Position(StreamIndexKey, BytesN<32>),
+}
+
+pub fn index_len(env: &Env, key: &StreamIndexKey) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&IndexEntryKey::Count(key.clone()))
+        .unwrap_or(0)
+}
+
+pub fn add_to_index(env: &Env, key: &StreamIndexKey, stream_id: &BytesN<32>) {
+    let len = index_len(env, key);
+    set_entry(env, key, len, stream_id);
+    set_len(env, key, len + 1);
+}
+
+// Appends several streams, writing the count once
+pub fn add_all_to_index(env: &Env, key: &StreamIndexKey, stream_ids: &Vec<BytesN<32>>) {
+    let mut len = index_len(env, key);
+    for stream_id in stream_ids.iter() {
+        set_entry(env, key, len, &stream_id);
+        len += 1;
+    }
+    set_len(env, key, len);
+}
+
+// Removes a stream by moving the last entry into its position, so listings
+// are in insertion order only until a stream is removed
+pub fn remove_from_index(env: &Env, key: &StreamIndexKey, stream_id: &BytesN<32>) {
+    let position_key = IndexEntryKey::Position(key.clone(), stream_id.clone());
+    let position: u32 = match env.storage().persistent().get(&position_key) {
+        Some(position) => position,
+        None => return,
+    };
+
+    let last = index_len(env, key) - 1;
+    if position != last {
+        let moved: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&IndexEntryKey::Entry(key.clone(), last))
+            .unwrap();
+        set_entry(env, key, position, &moved);
+    }
+    env.storage()
+        .persistent()
+        .remove(&IndexEntryKey::Entry(key.clone(), last));
+    env.storage().persistent().remove(&position_key);
+    set_len(env, key, last);
+}
+
+pub fn list_streams(env: &Env, key: &StreamIndexKey, offset: u32, limit: u32) -> Vec<BytesN<32>> {
+    let end = offset.saturating_add(limit).min(index_len(env, key));
+    let mut ids = Vec::new(env);
+    for position in offset..end {
+        let stream_id: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&IndexEntryKey::Entry(key.clone(), position))
+            .unwrap();
+        ids.push_back(stream_id);
+    }
+    ids
+}
+
+fn set_entry(env: &Env, key: &StreamIndexKey, position: u32, stream_id: &BytesN<32>) {
+    env.storage()
+        .persistent()
+        .set(&IndexEntryKey::Entry(key.clone(), position), stream_id);
+    env.storage().persistent().set(
+        &IndexEntryKey::Position(key.clone(), stream_id.clone()),
+        &position,
+    );
+}
+
+fn set_len(env: &Env, key: &StreamIndexKey, len: u32) {
+    env.storage()
+        .persistent()
+        .set(&IndexEntryKey::Count(key.clone()), &len);
+}
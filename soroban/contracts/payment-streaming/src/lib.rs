@@ -1,14 +1,17 @@
 #![no_std]
 use crate::voucher::SignedVoucher;
 
-use soroban_sdk::{contract, contractimpl, Address, BytesN, Env};
-use stream::{PaymentSchedule, StreamState};
+use index::StreamIndexKey;
+use soroban_sdk::{contract, contractimpl, Address, BytesN, Env, Vec};
+use stream::{PaymentSchedule, StreamRequest, StreamState};
 
 mod balance;
 mod channel;
 mod error;
+mod index;
 mod stream;
 #[cfg(test)]
+#[allow(clippy::assign_op_pattern, clippy::assertions_on_constants)]
 mod test;
 mod voucher;
 mod withdraw;
@@ -30,6 +33,18 @@ impl PaymentStreamingContract {
         stream::create_stream(&env, sender, recipient, total_amount, duration, schedule)
     }
 
+    pub fn create_streams_batch(
+        env: Env,
+        sender: Address,
+        requests: Vec<StreamRequest>,
+    ) -> Vec<BytesN<32>> {
+        stream::create_streams_batch(&env, sender, requests)
+    }
+
+    pub fn transfer_stream_recipient(env: Env, stream_id: BytesN<32>, new_recipient: Address) {
+        stream::transfer_recipient(&env, stream_id, new_recipient)
+    }
+
     pub fn withdraw_from_stream(env: Env, stream_id: BytesN<32>, amount: i128) {
         withdraw::withdraw_from_stream(&env, stream_id, amount)
     }
@@ -46,6 +61,24 @@ impl PaymentStreamingContract {
         balance::get_stream_balance(&env, stream_id)
     }
 
+    pub fn get_sender_streams(
+        env: Env,
+        sender: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<BytesN<32>> {
+        index::list_streams(&env, &StreamIndexKey::Sender(sender), offset, limit)
+    }
+
+    pub fn get_recipient_streams(
+        env: Env,
+        recipient: Address,
+        offset: u32,
+        limit: u32,
+    ) -> Vec<BytesN<32>> {
+        index::list_streams(&env, &StreamIndexKey::Recipient(recipient), offset, limit)
+    }
+
     // Channel functions
     pub fn open_channel(
         env: Env,
@@ -1,12 +1,12 @@
 #![cfg(test)]
 
 use crate::{
-    stream::{PaymentSchedule, TimeUnit},
+    stream::{PaymentSchedule, StreamRequest, TimeUnit, MAX_BATCH_SIZE},
     PaymentStreamingContract, PaymentStreamingContractClient,
 };
 use soroban_sdk::{
     testutils::{Address as _, Ledger},
-    Address, BytesN, Env,
+    vec, Address, BytesN, Env, Vec,
 };
 
 fn create_test_contract(env: &Env) -> PaymentStreamingContractClient<'_> {
@@ -212,6 +212,348 @@ fn test_stream_full_duration_completion() {
     assert_eq!(stream_state.available, 0);
 }
 
+#[test]
+fn test_block_based_stream_accrues_per_ledger() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    // Stream 1000 tokens over 100 ledgers
+    let total_amount = 1000i128;
+    let duration = 100u64;
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Blocks,
+        interval: 1,
+        release_rate: 10,
+    };
+
+    let stream_id =
+        contract.create_stream(&sender, &recipient, &total_amount, &duration, &schedule);
+
+    // Time passes without any ledger closing - nothing accrues
+    env.ledger().with_mut(|li| {
+        li.timestamp += 24 * 60 * 60;
+    });
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.available, 0);
+    assert_eq!(stream_state.ledgers_elapsed, 0);
+    assert_eq!(stream_state.seconds_elapsed, 24 * 60 * 60);
+
+    // 25 ledgers close, 5 seconds each
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 25;
+        li.timestamp += 25 * 5;
+    });
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.available, 250);
+    assert_eq!(stream_state.ledgers_elapsed, 25);
+    assert_eq!(stream_state.seconds_elapsed, 24 * 60 * 60 + 125);
+
+    contract.withdraw_from_stream(&stream_id, &250i128);
+    let result = contract.try_withdraw_from_stream(&stream_id, &1i128);
+    assert!(result.is_err());
+
+    // Past the final ledger everything is available
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 100;
+    });
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.available, total_amount - 250);
+}
+
+#[test]
+fn test_time_based_stream_reports_ledgers_elapsed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+
+    let total_amount = 1000i128;
+    let duration = 1000u64;
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 1,
+        release_rate: 1,
+    };
+
+    let stream_id =
+        contract.create_stream(&sender, &recipient, &total_amount, &duration, &schedule);
+
+    // Ledgers closing without time passing do not accrue a time-based stream
+    env.ledger().with_mut(|li| {
+        li.sequence_number += 50;
+    });
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.available, 0);
+    assert_eq!(stream_state.ledgers_elapsed, 50);
+    assert_eq!(stream_state.seconds_elapsed, 0);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 400;
+    });
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.available, 400);
+    assert_eq!(stream_state.seconds_elapsed, 400);
+}
+
+#[test]
+fn test_sender_and_recipient_stream_listings() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let alice = Address::generate(&env);
+    let bob = Address::generate(&env);
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 86400,
+        release_rate: 100,
+    };
+
+    let stream_1 = contract.create_stream(&sender, &alice, &1000i128, &1000u64, &schedule);
+    let stream_2 = contract.create_stream(&sender, &bob, &1000i128, &1000u64, &schedule);
+    let stream_3 = contract.create_stream(&sender, &alice, &1000i128, &1000u64, &schedule);
+
+    assert_eq!(
+        contract.get_sender_streams(&sender, &0, &10),
+        vec![&env, stream_1.clone(), stream_2.clone(), stream_3.clone()]
+    );
+    assert_eq!(
+        contract.get_recipient_streams(&alice, &0, &10),
+        vec![&env, stream_1, stream_3.clone()]
+    );
+    assert_eq!(
+        contract.get_recipient_streams(&bob, &0, &10),
+        vec![&env, stream_2.clone()]
+    );
+
+    // Pagination
+    assert_eq!(
+        contract.get_sender_streams(&sender, &1, &1),
+        vec![&env, stream_2]
+    );
+    assert_eq!(
+        contract.get_sender_streams(&sender, &2, &10),
+        vec![&env, stream_3]
+    );
+    assert_eq!(contract.get_sender_streams(&sender, &5, &10).len(), 0);
+}
+
+#[test]
+fn test_transfer_stream_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let new_recipient = Address::generate(&env);
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 1,
+        release_rate: 1,
+    };
+
+    let stream_id = contract.create_stream(&sender, &recipient, &1000i128, &1000u64, &schedule);
+
+    env.ledger().with_mut(|li| {
+        li.timestamp += 300;
+    });
+    contract.withdraw_from_stream(&stream_id, &300i128);
+
+    contract.transfer_stream_recipient(&stream_id, &new_recipient);
+
+    assert_eq!(contract.get_recipient_streams(&recipient, &0, &10).len(), 0);
+    assert_eq!(
+        contract.get_recipient_streams(&new_recipient, &0, &10),
+        vec![&env, stream_id.clone()]
+    );
+
+    // Later withdrawals are authorized by the new recipient
+    env.ledger().with_mut(|li| {
+        li.timestamp += 200;
+    });
+    contract.withdraw_from_stream(&stream_id, &200i128);
+    let auths = env.auths();
+    assert_eq!(auths[0].0, new_recipient);
+
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.withdrawn, 500);
+    assert_eq!(stream_state.available, 0);
+
+    // Transferring to the current recipient is rejected
+    let result = contract.try_transfer_stream_recipient(&stream_id, &new_recipient);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_transfer_settles_accrued_pay_to_current_recipient() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let new_recipient = Address::generate(&env);
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 1,
+        release_rate: 1,
+    };
+
+    let stream_id = contract.create_stream(&sender, &recipient, &1000i128, &1000u64, &schedule);
+
+    // 300 accrues to the original recipient, who has not withdrawn it yet
+    env.ledger().with_mut(|li| {
+        li.timestamp += 300;
+    });
+    contract.transfer_stream_recipient(&stream_id, &new_recipient);
+
+    // The accrued 300 is paid out to the original recipient, not handed over
+    let stream_state = contract.get_stream_balance(&stream_id);
+    assert_eq!(stream_state.withdrawn, 300);
+    assert_eq!(stream_state.available, 0);
+    let result = contract.try_withdraw_from_stream(&stream_id, &1i128);
+    assert!(result.is_err());
+
+    // Only pay accruing after the transfer goes to the new recipient
+    env.ledger().with_mut(|li| {
+        li.timestamp += 200;
+    });
+    let result = contract.try_withdraw_from_stream(&stream_id, &201i128);
+    assert!(result.is_err());
+    contract.withdraw_from_stream(&stream_id, &200i128);
+    assert_eq!(contract.get_stream_balance(&stream_id).withdrawn, 500);
+}
+
+#[test]
+fn test_transfer_keeps_other_recipient_streams_listed() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let sender = Address::generate(&env);
+    let recipient = Address::generate(&env);
+    let new_recipient = Address::generate(&env);
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 1,
+        release_rate: 1,
+    };
+
+    let stream_1 = contract.create_stream(&sender, &recipient, &1000i128, &1000u64, &schedule);
+    let stream_2 = contract.create_stream(&sender, &recipient, &1000i128, &1000u64, &schedule);
+    let stream_3 = contract.create_stream(&sender, &recipient, &1000i128, &1000u64, &schedule);
+
+    // Moving a stream out of the middle fills its slot with the last one
+    contract.transfer_stream_recipient(&stream_2, &new_recipient);
+    assert_eq!(
+        contract.get_recipient_streams(&recipient, &0, &10),
+        vec![&env, stream_1, stream_3.clone()]
+    );
+
+    contract.transfer_stream_recipient(&stream_3, &new_recipient);
+    assert_eq!(contract.get_recipient_streams(&recipient, &1, &10).len(), 0);
+    assert_eq!(
+        contract.get_recipient_streams(&new_recipient, &0, &10),
+        vec![&env, stream_2, stream_3]
+    );
+}
+
+#[test]
+fn test_create_streams_batch() {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract = create_test_contract(&env);
+    let employer = Address::generate(&env);
+    let schedule = PaymentSchedule {
+        unit: TimeUnit::Seconds,
+        interval: 86400,
+        release_rate: 100,
+    };
+
+    let mut requests = Vec::new(&env);
+    let mut employees = Vec::new(&env);
+    for i in 1..=MAX_BATCH_SIZE {
+        let employee = Address::generate(&env);
+        requests.push_back(StreamRequest {
+            recipient: employee.clone(),
+            total_amount: 1000 * i as i128,
+            duration: 30 * 24 * 60 * 60,
+            schedule: schedule.clone(),
+        });
+        employees.push_back(employee);
+    }
+
+    // A full batch stays within the per-transaction instruction and memory limits
+    env.cost_estimate().budget().reset_unlimited();
+    let stream_ids = contract.create_streams_batch(&employer, &requests);
+    assert_eq!(stream_ids.len(), MAX_BATCH_SIZE);
+    assert!(env.cost_estimate().budget().cpu_instruction_cost() < 100_000_000);
+    assert!(env.cost_estimate().budget().memory_bytes_cost() < 40 * 1024 * 1024);
+    assert_eq!(
+        contract.get_sender_streams(&employer, &0, &MAX_BATCH_SIZE),
+        stream_ids
+    );
+
+    for (i, employee) in employees.iter().enumerate() {
+        let listed = contract.get_recipient_streams(&employee, &0, &10);
+        assert_eq!(listed, vec![&env, stream_ids.get(i as u32).unwrap()]);
+        let state = contract.get_stream_balance(&listed.get(0).unwrap());
+        assert_eq!(state.total, 1000 * (i as i128 + 1));
+    }
+
+    // One invalid entry rejects the whole batch
+    let invalid = vec![
+        &env,
+        StreamRequest {
+            recipient: Address::generate(&env),
+            total_amount: 1000,
+            duration: 1000,
+            schedule: schedule.clone(),
+        },
+        StreamRequest {
+            recipient: Address::generate(&env),
+            total_amount: 0,
+            duration: 1000,
+            schedule: schedule.clone(),
+        },
+    ];
+    assert!(contract
+        .try_create_streams_batch(&employer, &invalid)
+        .is_err());
+    assert!(contract
+        .try_create_streams_batch(&employer, &Vec::new(&env))
+        .is_err());
+    assert_eq!(
+        contract
+            .get_sender_streams(&employer, &0, &(MAX_BATCH_SIZE + 1))
+            .len(),
+        MAX_BATCH_SIZE
+    );
+
+    // A run one over the cap is rejected
+    let mut oversized = Vec::new(&env);
+    for _ in 0..=MAX_BATCH_SIZE {
+        oversized.push_back(StreamRequest {
+            recipient: Address::generate(&env),
+            total_amount: 1000,
+            duration: 1000,
+            schedule: schedule.clone(),
+        });
+    }
+    assert!(contract
+        .try_create_streams_batch(&employer, &oversized)
+        .is_err());
+}
+
 #[test]
 fn test_micropayment_channel_opening() {
     let env = Env::default();
@@ -1,4 +1,7 @@
-use crate::{error::PaymentStreamingError, stream::Stream};
+use crate::{
+    error::PaymentStreamingError,
+    stream::{elapsed_units, Stream},
+};
 use soroban_sdk::{panic_with_error, BytesN, Env};
 
 pub fn withdraw_from_stream(env: &Env, stream_id: BytesN<32>, amount: i128) {
@@ -14,8 +17,7 @@ pub fn withdraw_from_stream(env: &Env, stream_id: BytesN<32>, amount: i128) {
         panic_with_error!(env, PaymentStreamingError::StreamNotActive);
     }
 
-    let current_time = env.ledger().timestamp();
-    let elapsed = current_time - stream.start_time;
+    let elapsed = elapsed_units(env, &stream);
     let available = calculate_available(&stream, elapsed);
 
     if amount > available {
@@ -26,7 +28,7 @@ pub fn withdraw_from_stream(env: &Env, stream_id: BytesN<32>, amount: i128) {
     env.storage().persistent().set(&stream_id, &stream);
 }
 
-fn calculate_available(stream: &Stream, elapsed: u64) -> i128 {
+pub fn calculate_available(stream: &Stream, elapsed: u64) -> i128 {
     if elapsed >= stream.duration {
         stream.total_amount - stream.withdrawn
     } else {
@@ -1,10 +1,12 @@
 use soroban_sdk::{Address, BytesN, Env, Map, String, Vec};
 
 use crate::{
-    utils, ContractStats, DataKey, EnergySource, EventType, IssuerInfo, RECEvent, RECStatus, REC,
+    matching, utils, ContractStats, DataKey, EnergySource, EventType, IssuerInfo, RECEvent,
+    RECStatus, REC,
 };
 
 /// Issues a new REC for verified renewable energy production
+#[allow(clippy::too_many_arguments)]
 pub fn issue_rec(
     env: &Env,
     issuer: Address,
@@ -15,6 +17,72 @@ pub fn issue_rec(
     verification_standard: String,
     verification_hash: BytesN<32>,
     metadata: Map<String, String>,
+) -> BytesN<32> {
+    issue(
+        env,
+        issuer,
+        energy_source,
+        production_date,
+        production_date,
+        production_location,
+        capacity_mwh,
+        verification_standard,
+        verification_hash,
+        metadata,
+    )
+}
+
+/// Issues a REC for renewable energy produced in the hour starting at
+/// `production_start`
+#[allow(clippy::too_many_arguments)]
+pub fn issue_hourly_rec(
+    env: &Env,
+    issuer: Address,
+    energy_source: EnergySource,
+    production_start: u64,
+    production_location: String,
+    capacity_mwh: i128,
+    verification_standard: String,
+    verification_hash: BytesN<32>,
+    metadata: Map<String, String>,
+) -> BytesN<32> {
+    if !utils::is_hour_boundary(production_start) {
+        panic!("Production start must be on an hour boundary");
+    }
+    // Certificates only cover generation that has already happened
+    if production_start + utils::HOUR_IN_SECONDS > env.ledger().timestamp() {
+        panic!("Production hour has not ended");
+    }
+
+    let rec_id = issue(
+        env,
+        issuer.clone(),
+        energy_source,
+        production_start,
+        production_start + utils::HOUR_IN_SECONDS,
+        production_location,
+        capacity_mwh,
+        verification_standard,
+        verification_hash,
+        metadata,
+    );
+    matching::add_hour_rec(env, &issuer, production_start, &rec_id);
+
+    rec_id
+}
+
+#[allow(clippy::too_many_arguments)]
+fn issue(
+    env: &Env,
+    issuer: Address,
+    energy_source: EnergySource,
+    production_start: u64,
+    production_end: u64,
+    production_location: String,
+    capacity_mwh: i128,
+    verification_standard: String,
+    verification_hash: BytesN<32>,
+    metadata: Map<String, String>,
 ) -> BytesN<32> {
     // Verify issuer is authorized
     issuer.require_auth();
@@ -49,7 +117,9 @@ pub fn issue_rec(
         id: rec_id.clone(),
         issuer: issuer.clone(),
         energy_source,
-        production_date,
+        production_date: production_start,
+        production_start,
+        production_end,
         production_location,
         capacity_mwh,
         current_owner: issuer.clone(),
@@ -58,6 +128,7 @@ pub fn issue_rec(
         verification_hash,
         issuance_date: env.ledger().timestamp(),
         metadata,
+        lineage: Vec::new(env),
     };
 
     // Store the REC
@@ -73,6 +144,7 @@ pub fn issue_rec(
         capacity_mwh,
         transaction_hash: BytesN::from_array(env, &[1u8; 32]),
         notes: String::from_str(env, "REC issued"),
+        linked_rec_ids: Vec::new(env),
     };
 
     // Store event in history
@@ -1,8 +1,11 @@
 #![no_std]
+// The argument builders generated for issuance functions take every issuance parameter
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{contract, contractimpl, contracttype, Address, BytesN, Env, Map, String, Vec};
 
 pub mod certificate;
+pub mod matching;
 pub mod transfer;
 pub mod utils;
 
@@ -17,6 +20,8 @@ pub struct REC {
     pub issuer: Address,
     pub energy_source: EnergySource,
     pub production_date: u64,
+    pub production_start: u64, // Start of the production hour for hourly RECs
+    pub production_end: u64,   // End of the production hour; equals the start for undated-hour RECs
     pub production_location: String,
     pub capacity_mwh: i128, // Megawatt-hours
     pub current_owner: Address,
@@ -25,6 +30,7 @@ pub struct REC {
     pub verification_hash: BytesN<32>, // Hash of verification documents
     pub issuance_date: u64,
     pub metadata: Map<String, String>, // Additional metadata
+    pub lineage: Vec<BytesN<32>>, // Ancestor RECs, from the original issuance down to the direct parent
 }
 
 #[contracttype]
@@ -58,6 +64,7 @@ pub struct RECEvent {
     pub capacity_mwh: i128,
     pub transaction_hash: BytesN<32>,
     pub notes: String,
+    pub linked_rec_ids: Vec<BytesN<32>>, // Child split off by this event, or the parent of a Split
 }
 
 #[contracttype]
@@ -67,6 +74,7 @@ pub enum EventType {
     Transfer,
     Retirement,
     Suspension,
+    Split,
 }
 
 #[contracttype]
@@ -97,6 +105,31 @@ pub struct RetirementParams {
     pub retirement_reason: String,
 }
 
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HourlyLoad {
+    pub hour_start: u64,
+    pub load_mwh: i128,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HourlyMatch {
+    pub hour_start: u64,
+    pub load_mwh: i128,
+    pub matched_mwh: i128,
+    pub retired_rec_ids: Vec<BytesN<32>>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MatchReport {
+    pub hours: Vec<HourlyMatch>,
+    pub total_load_mwh: i128,
+    pub total_matched_mwh: i128,
+    pub match_percentage_bps: u32, // Share of load matched hour by hour, 10000 = 100%
+}
+
 // Storage keys
 #[contracttype]
 #[derive(Clone)]
@@ -107,6 +140,8 @@ pub enum DataKey {
     Issuer(Address),
     RecHistory(BytesN<32>),
     Stats,
+    ChildRecs(BytesN<32>),
+    HourRecs(Address, u64), // (owner, hour start) -> usable hourly RECs
 }
 
 #[contracttype]
@@ -162,6 +197,7 @@ impl RenewableEnergyCertificateTracker {
     }
 
     /// Issue a new REC for verified renewable energy production
+    #[allow(clippy::too_many_arguments)]
     pub fn issue_rec(
         env: Env,
         issuer: Address,
@@ -186,28 +222,69 @@ impl RenewableEnergyCertificateTracker {
         )
     }
 
-    /// Transfer a REC to a new owner
+    /// Issue a REC for renewable energy produced in the hour starting at
+    /// `production_start`, which must fall on an hour boundary
+    #[allow(clippy::too_many_arguments)]
+    pub fn issue_hourly_rec(
+        env: Env,
+        issuer: Address,
+        energy_source: EnergySource,
+        production_start: u64,
+        production_location: String,
+        capacity_mwh: i128,
+        verification_standard: String,
+        verification_hash: BytesN<32>,
+        metadata: Map<String, String>,
+    ) -> BytesN<32> {
+        certificate::issue_hourly_rec(
+            &env,
+            issuer,
+            energy_source,
+            production_start,
+            production_location,
+            capacity_mwh,
+            verification_standard,
+            verification_hash,
+            metadata,
+        )
+    }
+
+    /// Transfer capacity of a REC to a new owner, splitting off a child
+    /// certificate for partial amounts. Returns the ID `to` now holds.
     pub fn transfer_rec(
         env: Env,
         rec_id: BytesN<32>,
         from: Address,
         to: Address,
         capacity_mwh: i128,
-    ) -> bool {
+    ) -> BytesN<32> {
         transfer::transfer_rec(&env, rec_id, from, to, capacity_mwh)
     }
 
-    /// Retire a REC to claim renewable energy usage
+    /// Retire capacity of a REC to claim renewable energy usage, splitting off
+    /// a retired child certificate for partial amounts. Returns the retired ID.
     pub fn retire_rec(
         env: Env,
         rec_id: BytesN<32>,
         owner: Address,
         capacity_mwh: i128,
         retirement_reason: String,
-    ) -> bool {
+    ) -> BytesN<32> {
         transfer::retire_rec(&env, rec_id, owner, capacity_mwh, retirement_reason)
     }
 
+    /// Match a consumer's hourly load against the hourly RECs they hold,
+    /// retiring certificates from the same hour as each load
+    pub fn match_hourly_load(env: Env, consumer: Address, loads: Vec<HourlyLoad>) -> MatchReport {
+        matching::match_hourly_load(&env, consumer, loads)
+    }
+
+    /// Get the hourly RECs an owner can still transfer or retire for the
+    /// hour starting at `hour_start`, including certificates split off them
+    pub fn get_hour_recs(env: Env, owner: Address, hour_start: u64) -> Vec<BytesN<32>> {
+        matching::get_hour_recs(&env, &owner, hour_start)
+    }
+
     /// Get the status and details of a REC
     pub fn get_rec_status(env: Env, rec_id: BytesN<32>) -> REC {
         let rec_key = DataKey::REC(rec_id);
@@ -223,6 +300,11 @@ impl RenewableEnergyCertificateTracker {
             .unwrap_or(Vec::new(&env))
     }
 
+    /// Get the certificates split off from a REC, oldest first
+    pub fn get_child_recs(env: Env, rec_id: BytesN<32>) -> Vec<BytesN<32>> {
+        transfer::get_child_recs(&env, rec_id)
+    }
+
     /// Get issuer information
     pub fn get_issuer_info(env: Env, issuer: Address) -> IssuerInfo {
         env.storage()
@@ -252,6 +334,9 @@ impl RenewableEnergyCertificateTracker {
 
         rec.status = RECStatus::Suspended;
         env.storage().persistent().set(&rec_key, &rec);
+        if utils::is_hourly(&rec) {
+            matching::remove_hour_rec(&env, &rec.current_owner, rec.production_start, &rec.id);
+        }
 
         // Log suspension event
         let event = RECEvent {
@@ -263,6 +348,7 @@ impl RenewableEnergyCertificateTracker {
             capacity_mwh: rec.capacity_mwh,
             transaction_hash: BytesN::from_array(&env, &[0u8; 32]),
             notes: String::from_str(&env, "REC suspended by admin"),
+            linked_rec_ids: Vec::new(&env),
         };
 
         let history_key = DataKey::RecHistory(rec.id.clone());
@@ -0,0 +1,113 @@
+use soroban_sdk::{Address, BytesN, Env, String, Vec};
+
+use crate::{transfer, utils, DataKey, HourlyLoad, HourlyMatch, MatchReport, REC};
+
+/// Basis points denominator for the match percentage
+const BASIS_POINTS: i128 = 10_000;
+
+/// Matches a consumer's hourly load against the hourly RECs they hold.
+///
+/// For each hour, the consumer's usable certificates produced in that hour are
+/// retired in the order they were acquired until the load is covered or they
+/// run out.
+/// Certificates from other hours never count towards a load. The report's
+/// percentage is the share of total load matched within its own hour.
+pub fn match_hourly_load(env: &Env, consumer: Address, loads: Vec<HourlyLoad>) -> MatchReport {
+    consumer.require_auth();
+
+    let mut hours = Vec::new(env);
+    let mut total_load_mwh = 0i128;
+    let mut total_matched_mwh = 0i128;
+
+    for load in loads.iter() {
+        if !utils::is_hour_boundary(load.hour_start) {
+            panic!("Load hour must start on an hour boundary");
+        }
+        if load.load_mwh < 0 {
+            panic!("Load cannot be negative");
+        }
+
+        let mut remaining = load.load_mwh;
+        let mut retired_rec_ids = Vec::new(env);
+        for rec_id in get_hour_recs(env, &consumer, load.hour_start).iter() {
+            if remaining == 0 {
+                break;
+            }
+
+            let rec: REC = env
+                .storage()
+                .persistent()
+                .get(&DataKey::REC(rec_id.clone()))
+                .unwrap();
+
+            let take = remaining.min(rec.capacity_mwh);
+            let retired_id = transfer::retire_owned(
+                env,
+                rec_id,
+                consumer.clone(),
+                take,
+                String::from_str(env, "Hourly load matching"),
+            );
+            retired_rec_ids.push_back(retired_id);
+            remaining -= take;
+        }
+
+        let matched_mwh = load.load_mwh - remaining;
+        total_load_mwh += load.load_mwh;
+        total_matched_mwh += matched_mwh;
+        hours.push_back(HourlyMatch {
+            hour_start: load.hour_start,
+            load_mwh: load.load_mwh,
+            matched_mwh,
+            retired_rec_ids,
+        });
+    }
+
+    let match_percentage_bps = if total_load_mwh > 0 {
+        (total_matched_mwh * BASIS_POINTS / total_load_mwh) as u32
+    } else {
+        0
+    };
+
+    MatchReport {
+        hours,
+        total_load_mwh,
+        total_matched_mwh,
+        match_percentage_bps,
+    }
+}
+
+/// Gets the hourly RECs `owner` can still transfer or retire for the hour
+/// starting at `hour_start`, in the order they were acquired
+pub fn get_hour_recs(env: &Env, owner: &Address, hour_start: u64) -> Vec<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::HourRecs(owner.clone(), hour_start))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Adds a usable hourly REC to its owner's index for its production hour
+pub fn add_hour_rec(env: &Env, owner: &Address, hour_start: u64, rec_id: &BytesN<32>) {
+    let mut recs = get_hour_recs(env, owner, hour_start);
+    recs.push_back(rec_id.clone());
+    set_hour_recs(env, owner, hour_start, &recs);
+}
+
+/// Removes an hourly REC from `owner`'s index once it is retired, suspended
+/// or handed over whole
+pub fn remove_hour_rec(env: &Env, owner: &Address, hour_start: u64, rec_id: &BytesN<32>) {
+    let mut recs = get_hour_recs(env, owner, hour_start);
+    if let Some(index) = recs.first_index_of(rec_id) {
+        recs.remove(index);
+        set_hour_recs(env, owner, hour_start, &recs);
+    }
+}
+
+fn set_hour_recs(env: &Env, owner: &Address, hour_start: u64, recs: &Vec<BytesN<32>>) {
+    let key = DataKey::HourRecs(owner.clone(), hour_start);
+    if recs.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, recs);
+    }
+}
@@ -1 +1,379 @@
-// placeholder
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    vec, Address, BytesN, Env, Map, String,
+};
+
+use crate::{
+    EnergySource, EventType, HourlyLoad, RECStatus, RenewableEnergyCertificateTracker,
+    RenewableEnergyCertificateTrackerClient,
+};
+
+fn setup() -> (
+    Env,
+    RenewableEnergyCertificateTrackerClient<'static>,
+    Address,
+) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(RenewableEnergyCertificateTracker, ());
+    let client = RenewableEnergyCertificateTrackerClient::new(&env, &contract_id);
+    let admin = Address::generate(&env);
+    client.initialize(&admin);
+
+    let issuer = Address::generate(&env);
+    client.register_issuer(&issuer, &String::from_str(&env, "Solar Farm Operator"));
+
+    (env, client, issuer)
+}
+
+fn issue(
+    env: &Env,
+    client: &RenewableEnergyCertificateTrackerClient,
+    issuer: &Address,
+    capacity_mwh: i128,
+) -> BytesN<32> {
+    client.issue_rec(
+        issuer,
+        &EnergySource::Solar,
+        &0u64,
+        &String::from_str(env, "Nevada"),
+        &capacity_mwh,
+        &String::from_str(env, "I-REC"),
+        &BytesN::from_array(env, &[1u8; 32]),
+        &Map::new(env),
+    )
+}
+
+fn issue_hourly(
+    env: &Env,
+    client: &RenewableEnergyCertificateTrackerClient,
+    issuer: &Address,
+    hour: u64,
+    capacity_mwh: i128,
+) -> BytesN<32> {
+    // Hourly RECs can only be issued once their hour is over
+    if env.ledger().timestamp() < (hour + 1) * 3_600 {
+        env.ledger()
+            .with_mut(|li| li.timestamp = (hour + 1) * 3_600);
+    }
+    client.issue_hourly_rec(
+        issuer,
+        &EnergySource::Wind,
+        &(hour * 3_600),
+        &String::from_str(env, "Texas"),
+        &capacity_mwh,
+        &String::from_str(env, "EnergyTag"),
+        &hourly_verification_hash(env, hour, capacity_mwh),
+        &Map::new(env),
+    )
+}
+
+fn hourly_verification_hash(env: &Env, hour: u64, capacity_mwh: i128) -> BytesN<32> {
+    let mut hash = [1u8; 32];
+    hash[0..8].copy_from_slice(&hour.to_be_bytes());
+    hash[8..24].copy_from_slice(&capacity_mwh.to_be_bytes());
+    BytesN::from_array(env, &hash)
+}
+
+fn load(hour: u64, load_mwh: i128) -> HourlyLoad {
+    HourlyLoad {
+        hour_start: hour * 3_600,
+        load_mwh,
+    }
+}
+
+#[test]
+fn test_partial_transfer_splits_child_rec() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let buyer = Address::generate(&env);
+    let child_id = client.transfer_rec(&rec_id, &issuer, &buyer, &1);
+    assert_ne!(child_id, rec_id);
+
+    // The sender keeps the remainder
+    let parent = client.get_rec_status(&rec_id);
+    assert_eq!(parent.capacity_mwh, 99);
+    assert_eq!(parent.current_owner, issuer);
+    assert_eq!(parent.status, RECStatus::Issued);
+
+    // The child carries the transferred MWh and its lineage
+    let child = client.get_rec_status(&child_id);
+    assert_eq!(child.capacity_mwh, 1);
+    assert_eq!(child.current_owner, buyer);
+    assert_eq!(child.status, RECStatus::Transferred);
+    assert_eq!(child.energy_source, EnergySource::Solar);
+    assert_eq!(child.verification_hash, parent.verification_hash);
+    assert_eq!(child.lineage, vec![&env, rec_id.clone()]);
+    assert_eq!(client.get_child_recs(&rec_id), vec![&env, child_id.clone()]);
+
+    // Both histories link parent and child
+    let parent_event = client.get_rec_history(&rec_id).last().unwrap();
+    assert_eq!(parent_event.event_type, EventType::Transfer);
+    assert_eq!(parent_event.capacity_mwh, 1);
+    assert_eq!(parent_event.linked_rec_ids, vec![&env, child_id.clone()]);
+    let child_event = client.get_rec_history(&child_id).get(0).unwrap();
+    assert_eq!(child_event.event_type, EventType::Split);
+    assert_eq!(child_event.linked_rec_ids, vec![&env, rec_id]);
+}
+
+#[test]
+fn test_full_transfer_moves_rec() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let buyer = Address::generate(&env);
+    assert_eq!(client.transfer_rec(&rec_id, &issuer, &buyer, &100), rec_id);
+
+    let rec = client.get_rec_status(&rec_id);
+    assert_eq!(rec.current_owner, buyer);
+    assert_eq!(rec.capacity_mwh, 100);
+    assert_eq!(client.get_child_recs(&rec_id).len(), 0);
+}
+
+#[test]
+fn test_child_rec_can_be_split_again() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let buyer = Address::generate(&env);
+    let other = Address::generate(&env);
+    let child_id = client.transfer_rec(&rec_id, &issuer, &buyer, &10);
+    let grandchild_id = client.transfer_rec(&child_id, &buyer, &other, &4);
+
+    assert_eq!(client.get_rec_status(&child_id).capacity_mwh, 6);
+    let grandchild = client.get_rec_status(&grandchild_id);
+    assert_eq!(grandchild.capacity_mwh, 4);
+    assert_eq!(grandchild.lineage, vec![&env, rec_id, child_id]);
+}
+
+#[test]
+fn test_partial_retirement_splits_retired_child() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let retired_id = client.retire_rec(
+        &rec_id,
+        &issuer,
+        &30,
+        &String::from_str(&env, "Scope 2 claim"),
+    );
+    assert_ne!(retired_id, rec_id);
+
+    let parent = client.get_rec_status(&rec_id);
+    assert_eq!(parent.capacity_mwh, 70);
+    assert_eq!(parent.status, RECStatus::Issued);
+
+    let retired = client.get_rec_status(&retired_id);
+    assert_eq!(retired.capacity_mwh, 30);
+    assert_eq!(retired.current_owner, issuer);
+    assert_eq!(retired.status, RECStatus::Retired);
+
+    // The remainder can still be transferred
+    let buyer = Address::generate(&env);
+    assert_eq!(client.transfer_rec(&rec_id, &issuer, &buyer, &70), rec_id);
+
+    let (_, _, transferred, retired_total) = client.get_contract_stats();
+    assert_eq!(transferred, 70);
+    assert_eq!(retired_total, 30);
+}
+
+#[test]
+fn test_full_retirement_retires_rec() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let retired_id = client.retire_rec(
+        &rec_id,
+        &issuer,
+        &100,
+        &String::from_str(&env, "Scope 2 claim"),
+    );
+    assert_eq!(retired_id, rec_id);
+    assert_eq!(client.get_rec_status(&rec_id).status, RECStatus::Retired);
+}
+
+#[test]
+#[should_panic(expected = "Not the owner")]
+fn test_sender_cannot_transfer_split_child() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let buyer = Address::generate(&env);
+    let child_id = client.transfer_rec(&rec_id, &issuer, &buyer, &10);
+    client.transfer_rec(&child_id, &issuer, &Address::generate(&env), &10);
+}
+
+#[test]
+#[should_panic(expected = "Insufficient capacity")]
+fn test_transfer_more_than_remaining() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue(&env, &client, &issuer, 100);
+
+    let buyer = Address::generate(&env);
+    client.transfer_rec(&rec_id, &issuer, &buyer, &60);
+    client.transfer_rec(&rec_id, &issuer, &buyer, &60);
+}
+
+#[test]
+fn test_hourly_rec_covers_one_hour() {
+    let (env, client, issuer) = setup();
+    let rec_id = issue_hourly(&env, &client, &issuer, 5, 20);
+
+    let rec = client.get_rec_status(&rec_id);
+    assert_eq!(rec.production_start, 18_000);
+    assert_eq!(rec.production_end, 21_600);
+    assert_eq!(rec.production_date, 18_000);
+    assert_eq!(
+        client.get_hour_recs(&issuer, &18_000),
+        vec![&env, rec_id.clone()]
+    );
+
+    // Split-off certificates certify the same hour for their new owner
+    let buyer = Address::generate(&env);
+    let child_id = client.transfer_rec(&rec_id, &issuer, &buyer, &5);
+    assert_eq!(client.get_rec_status(&child_id).production_start, 18_000);
+    assert_eq!(
+        client.get_hour_recs(&issuer, &18_000),
+        vec![&env, rec_id.clone()]
+    );
+    assert_eq!(
+        client.get_hour_recs(&buyer, &18_000),
+        vec![&env, child_id.clone()]
+    );
+
+    // Whole transfers move the entry, and retired or suspended RECs leave the index
+    client.transfer_rec(&rec_id, &issuer, &buyer, &15);
+    assert_eq!(client.get_hour_recs(&issuer, &18_000).len(), 0);
+    assert_eq!(
+        client.get_hour_recs(&buyer, &18_000),
+        vec![&env, child_id.clone(), rec_id.clone()]
+    );
+    client.retire_rec(&child_id, &buyer, &2, &String::from_str(&env, "Partial"));
+    client.retire_rec(&child_id, &buyer, &3, &String::from_str(&env, "Rest"));
+    client.suspend_rec(&rec_id);
+    assert_eq!(client.get_hour_recs(&buyer, &18_000).len(), 0);
+
+    // Undated-hour RECs stay out of the hourly index
+    let other_id = issue(&env, &client, &issuer, 100);
+    let other = client.get_rec_status(&other_id);
+    assert_eq!(other.production_start, other.production_end);
+    assert_eq!(client.get_hour_recs(&issuer, &0).len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Production start must be on an hour boundary")]
+fn test_hourly_rec_must_start_on_hour() {
+    let (env, client, issuer) = setup();
+    client.issue_hourly_rec(
+        &issuer,
+        &EnergySource::Wind,
+        &1_800,
+        &String::from_str(&env, "Texas"),
+        &10,
+        &String::from_str(&env, "EnergyTag"),
+        &BytesN::from_array(&env, &[9u8; 32]),
+        &Map::new(&env),
+    );
+}
+
+#[test]
+#[should_panic(expected = "Production hour has not ended")]
+fn test_hourly_rec_rejects_unfinished_hour() {
+    let (env, client, issuer) = setup();
+
+    // Half way through the hour starting at 7_200
+    env.ledger().with_mut(|li| li.timestamp = 9_000);
+    client.issue_hourly_rec(
+        &issuer,
+        &EnergySource::Wind,
+        &7_200,
+        &String::from_str(&env, "Texas"),
+        &10,
+        &String::from_str(&env, "EnergyTag"),
+        &BytesN::from_array(&env, &[9u8; 32]),
+        &Map::new(&env),
+    );
+}
+
+#[test]
+fn test_match_hourly_load_retires_same_hour_recs() {
+    let (env, client, issuer) = setup();
+    let consumer = Address::generate(&env);
+
+    let hour0_id = issue_hourly(&env, &client, &issuer, 0, 10);
+    let hour1_id = issue_hourly(&env, &client, &issuer, 1, 4);
+    let hour2_id = issue_hourly(&env, &client, &issuer, 2, 50);
+    client.transfer_rec(&hour0_id, &issuer, &consumer, &10);
+    let hour1_child = client.transfer_rec(&hour1_id, &issuer, &consumer, &3);
+
+    // Hour 2 production stays with the issuer, so the consumer's load there is unmatched
+    let report =
+        client.match_hourly_load(&consumer, &vec![&env, load(0, 6), load(1, 8), load(2, 5)]);
+
+    assert_eq!(report.total_load_mwh, 19);
+    assert_eq!(report.total_matched_mwh, 9);
+    assert_eq!(report.match_percentage_bps, 4_736);
+
+    let hour0 = report.hours.get(0).unwrap();
+    assert_eq!(hour0.matched_mwh, 6);
+    let hour0_retired = hour0.retired_rec_ids.get(0).unwrap();
+    assert_eq!(
+        client.get_rec_status(&hour0_retired).status,
+        RECStatus::Retired
+    );
+    assert_eq!(client.get_rec_status(&hour0_retired).capacity_mwh, 6);
+    assert_eq!(client.get_rec_status(&hour0_id).capacity_mwh, 4);
+
+    let hour1 = report.hours.get(1).unwrap();
+    assert_eq!(hour1.matched_mwh, 3);
+    assert_eq!(hour1.retired_rec_ids, vec![&env, hour1_child.clone()]);
+    assert_eq!(
+        client.get_rec_status(&hour1_child).status,
+        RECStatus::Retired
+    );
+    assert_eq!(client.get_rec_status(&hour1_id).status, RECStatus::Issued);
+
+    assert_eq!(report.hours.get(2).unwrap().matched_mwh, 0);
+    assert_eq!(client.get_rec_status(&hour2_id).capacity_mwh, 50);
+
+    let (_, _, _, retired_total) = client.get_contract_stats();
+    assert_eq!(retired_total, 9);
+}
+
+#[test]
+fn test_match_hourly_load_fully_covered() {
+    let (env, client, issuer) = setup();
+    let consumer = Address::generate(&env);
+
+    let first_id = issue_hourly(&env, &client, &issuer, 3, 2);
+    let second_id = issue_hourly(&env, &client, &issuer, 3, 5);
+    client.transfer_rec(&first_id, &issuer, &consumer, &2);
+    client.transfer_rec(&second_id, &issuer, &consumer, &5);
+
+    let report = client.match_hourly_load(&consumer, &vec![&env, load(3, 4)]);
+    assert_eq!(report.match_percentage_bps, 10_000);
+    assert_eq!(report.hours.get(0).unwrap().retired_rec_ids.len(), 2);
+    assert_eq!(client.get_rec_status(&first_id).status, RECStatus::Retired);
+    assert_eq!(client.get_rec_status(&second_id).capacity_mwh, 3);
+}
+
+#[test]
+#[should_panic(expected = "Load hour must start on an hour boundary")]
+fn test_match_hourly_load_rejects_unaligned_hour() {
+    let (env, client, _) = setup();
+    let consumer = Address::generate(&env);
+    client.match_hourly_load(
+        &consumer,
+        &vec![
+            &env,
+            HourlyLoad {
+                hour_start: 60,
+                load_mwh: 1,
+            },
+        ],
+    );
+}
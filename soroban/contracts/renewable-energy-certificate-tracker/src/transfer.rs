@@ -1,15 +1,19 @@
 use soroban_sdk::{Address, BytesN, Env, String, Vec};
 
-use crate::{ContractStats, DataKey, EventType, RECEvent, RECStatus, REC};
+use crate::{matching, utils, ContractStats, DataKey, EventType, RECEvent, RECStatus, REC};
 
-/// Transfers a REC to a new owner
+/// Transfers `capacity_mwh` of a REC to a new owner.
+///
+/// Transferring the whole remaining capacity moves the REC itself. A smaller
+/// amount is split off into a child certificate owned by `to`, and the sender
+/// keeps the remainder. Returns the ID of the certificate `to` now holds.
 pub fn transfer_rec(
     env: &Env,
     rec_id: BytesN<32>,
     from: Address,
     to: Address,
     capacity_mwh: i128,
-) -> bool {
+) -> BytesN<32> {
     // Require authentication from sender
     from.require_auth();
 
@@ -49,9 +53,21 @@ pub fn transfer_rec(
         panic!("Cannot transfer to self");
     }
 
-    // Update REC ownership and status
-    rec.current_owner = to.clone();
-    rec.status = RECStatus::Transferred;
+    // Move the whole REC, or split the transferred capacity into a child
+    let mut linked_rec_ids = Vec::new(env);
+    let transferred_id = if capacity_mwh == rec.capacity_mwh {
+        rec.current_owner = to.clone();
+        rec.status = RECStatus::Transferred;
+        if utils::is_hourly(&rec) {
+            matching::remove_hour_rec(env, &from, rec.production_start, &rec_id);
+            matching::add_hour_rec(env, &to, rec.production_start, &rec_id);
+        }
+        rec_id.clone()
+    } else {
+        let child_id = split_rec(env, &mut rec, &to, capacity_mwh, RECStatus::Transferred);
+        linked_rec_ids.push_back(child_id.clone());
+        child_id
+    };
     env.storage().persistent().set(&rec_key, &rec);
 
     // Create transfer event
@@ -64,37 +80,45 @@ pub fn transfer_rec(
         capacity_mwh,
         transaction_hash: BytesN::from_array(env, &[2u8; 32]),
         notes: String::from_str(env, "REC transferred"),
+        linked_rec_ids,
     };
-
-    // Add event to history
-    let history_key = DataKey::RecHistory(rec_id);
-    let mut history: Vec<RECEvent> = env
-        .storage()
-        .persistent()
-        .get(&history_key)
-        .unwrap_or(Vec::new(env));
-    history.push_back(event);
-    env.storage().persistent().set(&history_key, &history);
+    append_history(env, &rec_id, event);
 
     // Update contract statistics
     let mut stats: ContractStats = env.storage().instance().get(&DataKey::Stats).unwrap();
     stats.total_capacity_transferred_mwh += capacity_mwh;
     env.storage().instance().set(&DataKey::Stats, &stats);
 
-    true
+    transferred_id
 }
 
-/// Retires a REC to claim renewable energy usage
+/// Retires `capacity_mwh` of a REC to claim renewable energy usage.
+///
+/// Retiring the whole remaining capacity retires the REC itself. A smaller
+/// amount is split off into a retired child certificate, and the owner keeps
+/// the remainder. Returns the ID of the retired certificate.
 pub fn retire_rec(
     env: &Env,
     rec_id: BytesN<32>,
     owner: Address,
     capacity_mwh: i128,
     retirement_reason: String,
-) -> bool {
+) -> BytesN<32> {
     // Require authentication from owner
     owner.require_auth();
 
+    retire_owned(env, rec_id, owner, capacity_mwh, retirement_reason)
+}
+
+/// Retires `capacity_mwh` of a REC on behalf of an owner who has already
+/// authorized the call. Returns the ID of the retired certificate.
+pub fn retire_owned(
+    env: &Env,
+    rec_id: BytesN<32>,
+    owner: Address,
+    capacity_mwh: i128,
+    retirement_reason: String,
+) -> BytesN<32> {
     // Get the REC
     let rec_key = DataKey::REC(rec_id.clone());
     let mut rec: REC = env
@@ -127,8 +151,19 @@ pub fn retire_rec(
         panic!("Insufficient capacity");
     }
 
-    // Update REC status
-    rec.status = RECStatus::Retired;
+    // Retire the whole REC, or split the retired capacity into a child
+    let mut linked_rec_ids = Vec::new(env);
+    let retired_id = if capacity_mwh == rec.capacity_mwh {
+        rec.status = RECStatus::Retired;
+        if utils::is_hourly(&rec) {
+            matching::remove_hour_rec(env, &owner, rec.production_start, &rec_id);
+        }
+        rec_id.clone()
+    } else {
+        let child_id = split_rec(env, &mut rec, &owner, capacity_mwh, RECStatus::Retired);
+        linked_rec_ids.push_back(child_id.clone());
+        child_id
+    };
     env.storage().persistent().set(&rec_key, &rec);
 
     // Create retirement event
@@ -141,10 +176,86 @@ pub fn retire_rec(
         capacity_mwh,
         transaction_hash: BytesN::from_array(env, &[3u8; 32]),
         notes: retirement_reason,
+        linked_rec_ids,
     };
+    append_history(env, &rec_id, event);
 
-    // Add event to history
-    let history_key = DataKey::RecHistory(rec_id);
+    // Update contract statistics
+    let mut stats: ContractStats = env.storage().instance().get(&DataKey::Stats).unwrap();
+    stats.total_capacity_retired_mwh += capacity_mwh;
+    env.storage().instance().set(&DataKey::Stats, &stats);
+
+    retired_id
+}
+
+/// Gets the certificates split off from a REC, oldest first
+pub fn get_child_recs(env: &Env, rec_id: BytesN<32>) -> Vec<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::ChildRecs(rec_id))
+        .unwrap_or(Vec::new(env))
+}
+
+/// Splits `capacity_mwh` off `parent` into a child certificate owned by
+/// `owner`, recording the split in the child's history. The caller stores the
+/// reduced parent.
+fn split_rec(
+    env: &Env,
+    parent: &mut REC,
+    owner: &Address,
+    capacity_mwh: i128,
+    status: RECStatus,
+) -> BytesN<32> {
+    let mut children = get_child_recs(env, parent.id.clone());
+    let child_id = utils::generate_child_rec_id(env, &parent.id, children.len());
+
+    let mut lineage = parent.lineage.clone();
+    lineage.push_back(parent.id.clone());
+
+    let mut child = parent.clone();
+    child.id = child_id.clone();
+    child.capacity_mwh = capacity_mwh;
+    child.current_owner = owner.clone();
+    child.status = status;
+    child.lineage = lineage;
+    env.storage()
+        .persistent()
+        .set(&DataKey::REC(child_id.clone()), &child);
+
+    parent.capacity_mwh -= capacity_mwh;
+
+    children.push_back(child_id.clone());
+    env.storage()
+        .persistent()
+        .set(&DataKey::ChildRecs(parent.id.clone()), &children);
+
+    // Usable children of an hourly REC certify the same hour for their owner
+    if utils::is_hourly(parent) && child.status != RECStatus::Retired {
+        matching::add_hour_rec(env, owner, parent.production_start, &child_id);
+    }
+
+    // The child's history starts with where it came from
+    let mut linked_rec_ids = Vec::new(env);
+    linked_rec_ids.push_back(parent.id.clone());
+    let event = RECEvent {
+        event_type: EventType::Split,
+        rec_id: child_id.clone(),
+        timestamp: env.ledger().timestamp(),
+        from: Some(parent.current_owner.clone()),
+        to: Some(owner.clone()),
+        capacity_mwh,
+        transaction_hash: BytesN::from_array(env, &[4u8; 32]),
+        notes: String::from_str(env, "Split from parent REC"),
+        linked_rec_ids,
+    };
+    append_history(env, &child_id, event);
+
+    child_id
+}
+
+/// Appends an event to a REC's history
+fn append_history(env: &Env, rec_id: &BytesN<32>, event: RECEvent) {
+    let history_key = DataKey::RecHistory(rec_id.clone());
     let mut history: Vec<RECEvent> = env
         .storage()
         .persistent()
@@ -152,13 +263,6 @@ pub fn retire_rec(
         .unwrap_or(Vec::new(env));
     history.push_back(event);
     env.storage().persistent().set(&history_key, &history);
-
-    // Update contract statistics
-    let mut stats: ContractStats = env.storage().instance().get(&DataKey::Stats).unwrap();
-    stats.total_capacity_retired_mwh += capacity_mwh;
-    env.storage().instance().set(&DataKey::Stats, &stats);
-
-    true
 }
 
 /// Checks if a REC can be transferred
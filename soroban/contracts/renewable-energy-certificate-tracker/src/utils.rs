@@ -1,5 +1,10 @@
 use soroban_sdk::{Address, Bytes, BytesN, Env};
 
+use crate::REC;
+
+/// Length of the production window of an hourly REC
+pub const HOUR_IN_SECONDS: u64 = 3_600;
+
 /// Generates a unique REC ID based on issuer and verification hash
 pub fn generate_rec_id(env: &Env, _issuer: &Address, verification_hash: &BytesN<32>) -> BytesN<32> {
     let timestamp = env.ledger().timestamp();
@@ -14,6 +19,27 @@ pub fn generate_rec_id(env: &Env, _issuer: &Address, verification_hash: &BytesN<
     env.crypto().sha256(&bytes_data).into()
 }
 
+/// Derives the ID of a certificate split off `parent_id` from its position
+/// among the parent's children
+pub fn generate_child_rec_id(env: &Env, parent_id: &BytesN<32>, index: u32) -> BytesN<32> {
+    let mut combined = [0u8; 36];
+    combined[0..32].copy_from_slice(&parent_id.to_array());
+    combined[32..36].copy_from_slice(&index.to_be_bytes());
+
+    let bytes_data = Bytes::from_slice(env, &combined);
+    env.crypto().sha256(&bytes_data).into()
+}
+
+/// Checks that a timestamp starts an hour
+pub fn is_hour_boundary(timestamp: u64) -> bool {
+    timestamp.is_multiple_of(HOUR_IN_SECONDS)
+}
+
+/// Checks whether a REC certifies a single production hour
+pub fn is_hourly(rec: &REC) -> bool {
+    rec.production_end == rec.production_start + HOUR_IN_SECONDS
+}
+
 /// Validates energy capacity value
 pub fn validate_capacity(capacity_mwh: i128) -> bool {
     capacity_mwh > 0
@@ -95,6 +121,13 @@ mod tests {
         assert!(!validate_transfer(100, &addr1, &addr1));
     }
 
+    #[test]
+    fn test_is_hour_boundary() {
+        assert!(is_hour_boundary(0));
+        assert!(is_hour_boundary(7_200));
+        assert!(!is_hour_boundary(7_201));
+    }
+
     #[test]
     fn test_validate_retirement() {
         assert!(validate_retirement(100));
@@ -116,8 +149,6 @@ mod tests {
 
     #[test]
     fn test_generate_rec_id() {
-        use soroban_sdk::testutils::Ledger as _;
-
         let env = Env::default();
         let issuer = Address::generate(&env);
         let verification_hash1 = BytesN::from_array(&env, &[1u8; 32]);